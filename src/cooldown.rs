@@ -0,0 +1,70 @@
+//! Module containing cooldown types used in Lootr.
+//!
+//! A [`CooldownTracker`](crate::cooldown::CooldownTracker) remembers when items were last dropped,
+//! so they can be kept out of rolls until their cooldown elapsed.
+//!
+//! Time is never read from the system clock, callers provide `now` explicitly.
+//!
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Holds the last drop time of items, by name.
+///
+/// Used with [`Lootr::roll_with_cooldown()`](crate::Lootr::roll_with_cooldown).
+///
+#[derive(Debug, Clone)]
+pub struct CooldownTracker<'a> {
+    /// Holds the time an item must wait before it can drop again.
+    ///
+    pub cooldown: Duration,
+
+    last_drops: HashMap<Cow<'a, str>, Instant>,
+}
+
+impl<'a> CooldownTracker<'a> {
+    /// Create a tracker with the given cooldown.
+    ///
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            last_drops: HashMap::new(),
+        }
+    }
+
+    /// Check if the given item name can drop at `now`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::cooldown::CooldownTracker;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let now = Instant::now();
+    /// let mut tracker = CooldownTracker::new(Duration::from_secs(60));
+    ///
+    /// tracker.record("chest", now);
+    ///
+    /// assert_eq!(tracker.is_ready("chest", now + Duration::from_secs(10)), false);
+    /// assert_eq!(tracker.is_ready("chest", now + Duration::from_secs(60)), true);
+    /// ```
+    pub fn is_ready(&self, name: &str, now: Instant) -> bool {
+        match self.last_drops.get(name) {
+            None => true,
+            Some(last) => now.saturating_duration_since(*last) >= self.cooldown,
+        }
+    }
+
+    /// Record a drop of the given item name at `now`.
+    ///
+    pub fn record<N>(&mut self, name: N, now: Instant) -> &mut Self
+    where
+        N: Into<Cow<'a, str>>,
+    {
+        self.last_drops.insert(name.into(), now);
+        self
+    }
+}
@@ -8,8 +8,8 @@
 //! The easiest way to create a Drop is to use [`DropBuilder`](crate::drops::DropBuilder), the Lootr builder pattern for Drop.
 //!
 
-use crate::ROOT;
-use std::ops::RangeInclusive;
+use crate::{item::Item, rarity::Rarity, LootrError, ROOT};
+use std::{fmt, ops::RangeInclusive};
 
 /// Holds a Lootr Drop.
 ///
@@ -20,11 +20,21 @@ use std::ops::RangeInclusive;
 ///
 /// The easiest way to create a Drop is to use [`DropBuilder`](crate::drops::DropBuilder), the Lootr builder pattern for Drop.
 ///
+/// Drops borrow their names, `path`, `item`, `requires` and `require_tag`, for the `'a` lifetime.
+/// With the `serde` feature, drops can be serialized, and deserialized drops borrow their names from the input.
+/// Parsed drops borrow their path from the spec, see [`Drop::parse()`](crate::drops::Drop::parse).
+///
 #[derive(Clone)]
-pub struct Drop {
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Drop<'a> {
     /// Holds the root path to drop from.
     ///
-    pub path: Option<&'static str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub path: Option<&'a str>,
 
     /// Holds the drop starting depth.
     /// Will decrease at each visited sub-branch.
@@ -40,20 +50,215 @@ pub struct Drop {
     ///
     pub stack: RangeInclusive<u32>,
 
+    /// Holds an optional discrete stack distribution, as `(size, weight)` pairs.
+    /// When present, it overrides the `stack` range.
+    ///
+    pub stack_dist: Option<Vec<(u32, f32)>>,
+
     /// If true, will yield modified Items.
     /// See [Modifiers](crate::Modifier)
     ///
     pub modify: bool,
+
+    /// Holds the name of a specific item to yield, instead of a random one.
+    /// The item is looked up anywhere under `path`, and still subject to `luck`.
+    ///
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub item: Option<&'a str>,
+
+    /// Holds the keys required to unlock this drop.
+    /// See [`Lootr::loot_with_inventory()`](crate::Lootr::loot_with_inventory)
+    ///
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub requires: Vec<&'a str>,
+
+    /// If true, yields a single item, never named like an item already looted in the same call.
+    ///
+    pub unique: bool,
+
+    /// If true, a drop yielding nothing is rolled again, ignoring `luck`, until it yields an item.
+    /// Attempts are bounded, so drops on empty branchs still yield nothing.
+    ///
+    pub guaranteed: bool,
+
+    /// Holds an optional predicate checked against the picked item.
+    /// If the item does not satisfy it, the drop yields nothing.
+    ///
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub filter: Option<fn(&Item) -> bool>,
+
+    /// Holds an optional target rarity.
+    /// Items of this tier are picked instead of the others, if any can be reached from `path` within `depth`.
+    ///
+    pub rarity: Option<Rarity>,
+
+    /// Holds an optional tag, only items carrying it can be yielded.
+    ///
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub require_tag: Option<&'a str>,
 }
 
-impl Default for Drop {
+impl Default for Drop<'_> {
     fn default() -> Self {
         Self {
             path: ROOT,
             depth: 1,
             luck: 1.0,
             stack: 1..=1,
+            stack_dist: None,
             modify: false,
+            item: None,
+            requires: vec![],
+            unique: false,
+            guaranteed: false,
+            filter: None,
+            rarity: None,
+            require_tag: None,
+        }
+    }
+}
+
+impl<'a> Drop<'a> {
+    /// Create a Drop from a compact spec, like `weapons@3 luck=0.5 x1-3`.
+    ///
+    /// The spec holds whitespace separated tokens, all optional:
+    /// - `path@depth`, the path and depth to drop from. `path`, `@depth` and `@*` (any depth) are accepted too.
+    /// - `luck=L`, the drop luck.
+    /// - `xA-B`, the stack range, or `xN` for a fixed stack.
+    ///
+    /// Missing tokens keep their [`Drop::default()`](crate::drops::Drop::default) values.
+    /// The drop path borrows from `spec`, so specs can be read at runtime, e.g. from a data file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::drops::Drop;
+    ///
+    /// let drop = Drop::parse("weapons@3 luck=0.5 x1-3").unwrap();
+    ///
+    /// assert_eq!(drop.path, Some("weapons"));
+    /// assert_eq!(drop.depth, 3);
+    /// assert_eq!(drop.luck, 0.5);
+    /// assert_eq!(drop.stack, 1..=3);
+    /// ```
+    pub fn parse(spec: &'a str) -> Result<Drop<'a>, ParseError> {
+        let mut drop = Drop::default();
+        let mut has_path = false;
+
+        for token in spec.split_whitespace() {
+            if let Some(luck) = token.strip_prefix("luck=") {
+                drop.luck = match luck.parse::<f32>() {
+                    Ok(luck) if luck.is_finite() && luck >= 0.0 => luck,
+                    _ => return Err(ParseError::InvalidLuck(token.to_string())),
+                };
+            } else if let Some(stack) = token
+                .strip_prefix('x')
+                .filter(|stack| stack.starts_with(|c: char| c.is_ascii_digit()))
+            {
+                let (min, max) = stack.split_once('-').unwrap_or((stack, stack));
+
+                drop.stack = match (min.parse::<u32>(), max.parse::<u32>()) {
+                    (Ok(min), Ok(max)) if min <= max => min..=max,
+                    _ => return Err(ParseError::InvalidStack(token.to_string())),
+                };
+            } else if !has_path && !token.contains('=') {
+                let (path, depth) = match token.split_once('@') {
+                    None => (token, None),
+                    Some((path, depth)) => (path, Some(depth)),
+                };
+
+                drop.path = match path.trim_matches('/') {
+                    "" => ROOT,
+                    path => Some(path),
+                };
+                drop.depth = match depth {
+                    None => drop.depth,
+                    Some("*") => i16::MAX,
+                    Some(depth) => depth
+                        .parse::<i16>()
+                        .map_err(|_| ParseError::InvalidDepth(token.to_string()))?,
+                };
+                has_path = true;
+            } else {
+                return Err(ParseError::UnexpectedToken(token.to_string()));
+            }
+        }
+
+        Ok(drop)
+    }
+}
+
+/// Holds an error met while parsing a [`Drop`](crate::drops::Drop) spec, with the offending token.
+///
+/// See [`Drop::parse()`](crate::drops::Drop::parse)
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The `@depth` part of a path token is neither a number nor `*`.
+    ///
+    InvalidDepth(String),
+
+    /// The `luck=` token does not hold a finite, positive number.
+    ///
+    InvalidLuck(String),
+
+    /// The `x` token does not hold a stack size, or an ordered `min-max` range.
+    ///
+    InvalidStack(String),
+
+    /// The token is not a known option, or a second path.
+    ///
+    UnexpectedToken(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidDepth(token) => write!(f, "invalid depth: {token}"),
+            ParseError::InvalidLuck(token) => write!(f, "invalid luck: {token}"),
+            ParseError::InvalidStack(token) => write!(f, "invalid stack: {token}"),
+            ParseError::UnexpectedToken(token) => write!(f, "unexpected token: {token}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Holds a Lootr DropSet.
+///
+/// DropSets describe a group of items which are looted all together, or not at all.
+///
+/// When used in [`Lootr::loot_sets()`](crate::Lootr::loot_sets), every named item is yield
+/// on a successful roll against `luck`.
+///
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct DropSet<'a> {
+    /// Holds the root path to look the items up from.
+    ///
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub path: Option<&'a str>,
+
+    /// Holds the names of the set items.
+    ///
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub items: Vec<&'a str>,
+
+    /// Holds the chances to yield the set.
+    ///
+    pub luck: f32,
+}
+
+impl Default for DropSet<'_> {
+    fn default() -> Self {
+        Self {
+            path: ROOT,
+            items: vec![],
+            luck: 1.0,
         }
     }
 }
@@ -67,7 +272,15 @@ pub struct DropBuilder {
     pub depth: i16,
     pub luck: f32,
     pub stack: RangeInclusive<u32>,
+    pub stack_dist: Option<Vec<(u32, f32)>>,
     pub modify: bool,
+    pub item: Option<&'static str>,
+    pub requires: Vec<&'static str>,
+    pub unique: bool,
+    pub guaranteed: bool,
+    pub filter: Option<fn(&Item) -> bool>,
+    pub rarity: Option<Rarity>,
+    pub require_tag: Option<&'static str>,
 }
 
 impl Default for DropBuilder {
@@ -83,7 +296,15 @@ impl DropBuilder {
             depth: 1,
             luck: f32::MAX,
             stack: 1..=1,
+            stack_dist: None,
             modify: false,
+            item: None,
+            requires: vec![],
+            unique: false,
+            guaranteed: false,
+            filter: None,
+            rarity: None,
+            require_tag: None,
         }
     }
 
@@ -175,6 +396,65 @@ impl DropBuilder {
         self
     }
 
+    /// Set the `stack_dist` for the future [`Drop`](crate::drops::Drop) object.
+    /// It overrides the `stack` range.
+    ///
+    /// Weights are normalized if they don't sum to 1.
+    ///
+    /// Returns an error if a weight is negative or not finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::drops::DropBuilder;
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .stack_dist(vec![(1, 8.0), (2, 2.0)])
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// assert_eq!(drop.stack_dist, Some(vec![(1, 0.8), (2, 0.2)]));
+    /// assert!(DropBuilder::new().stack_dist(vec![(1, -1.0)]).is_err());
+    /// ```
+    pub fn stack_dist(mut self, dist: Vec<(u32, f32)>) -> Result<DropBuilder, LootrError> {
+        if let Some((size, weight)) = dist
+            .iter()
+            .find(|(_, weight)| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err(LootrError::InvalidStackDist(format!("({size}, {weight})")));
+        }
+
+        let total: f32 = dist.iter().map(|(_, weight)| weight).sum();
+        let dist = match total > 0.0 && (total - 1.0).abs() > f32::EPSILON {
+            true => dist
+                .into_iter()
+                .map(|(size, weight)| (size, weight / total))
+                .collect(),
+            false => dist,
+        };
+
+        self.stack_dist = Some(dist);
+        Ok(self)
+    }
+
+    /// Set the `modify` flag for the future [`Drop`](crate::drops::Drop) object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::drops::DropBuilder;
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .modify(true)
+    ///     .build();
+    ///
+    /// assert_eq!(drop.modify, true);
+    /// ```
+    pub fn modify(mut self, modify: bool) -> DropBuilder {
+        self.modify = modify;
+        self
+    }
+
     /// Set the `modify` flag to true, for the future [`Drop`](crate::drops::Drop) object.
     ///
     /// # Examples
@@ -183,11 +463,140 @@ impl DropBuilder {
     /// use lootr::drops::DropBuilder;
     ///
     /// let drop = DropBuilder::new()
-    ///     .modify()
+    ///     .modified()
+    ///     .build();
+    ///
+    /// assert_eq!(drop.modify, true);
+    /// ```
+    pub fn modified(self) -> DropBuilder {
+        self.modify(true)
+    }
+
+    /// Set the `item` to yield for the future [`Drop`](crate::drops::Drop) object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::drops::DropBuilder;
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .path("weapons")
+    ///     .item("Uzi")
+    ///     .build();
+    ///
+    /// assert_eq!(drop.item, Some("Uzi"));
+    /// ```
+    pub fn item(mut self, name: &'static str) -> DropBuilder {
+        self.item = Some(name);
+        self
+    }
+
+    /// Add a required key for the future [`Drop`](crate::drops::Drop) object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::drops::DropBuilder;
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .requires("golden_key")
+    ///     .build();
+    ///
+    /// assert_eq!(drop.requires, vec!["golden_key"]);
+    /// ```
+    pub fn requires(mut self, key: &'static str) -> DropBuilder {
+        self.requires.push(key);
+        self
+    }
+
+    /// Set the `unique` flag for the future [`Drop`](crate::drops::Drop) object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::drops::DropBuilder;
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .unique(true)
+    ///     .build();
+    ///
+    /// assert_eq!(drop.unique, true);
+    /// ```
+    pub fn unique(mut self, unique: bool) -> DropBuilder {
+        self.unique = unique;
+        self
+    }
+
+    /// Set the `guaranteed` flag for the future [`Drop`](crate::drops::Drop) object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::drops::DropBuilder;
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .luck(0.01)
+    ///     .guaranteed(true)
+    ///     .build();
+    ///
+    /// assert_eq!(drop.guaranteed, true);
+    /// ```
+    pub fn guaranteed(mut self, guaranteed: bool) -> DropBuilder {
+        self.guaranteed = guaranteed;
+        self
+    }
+
+    /// Set the `filter` predicate for the future [`Drop`](crate::drops::Drop) object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::drops::DropBuilder;
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .filter(|item| item.has_prop("attack"))
+    ///     .build();
+    ///
+    /// assert!(drop.filter.is_some());
+    /// ```
+    pub fn filter(mut self, filter: fn(&Item) -> bool) -> DropBuilder {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Set the target `rarity` for the future [`Drop`](crate::drops::Drop) object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::{drops::DropBuilder, rarity::Rarity};
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .rarity(Rarity::Legendary)
+    ///     .build();
+    ///
+    /// assert_eq!(drop.rarity, Some(Rarity::Legendary));
+    /// ```
+    pub fn rarity(mut self, rarity: Rarity) -> DropBuilder {
+        self.rarity = Some(rarity);
+        self
+    }
+
+    /// Set the `require_tag` for the future [`Drop`](crate::drops::Drop) object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::drops::DropBuilder;
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .require_tag("rare")
     ///     .build();
+    ///
+    /// assert_eq!(drop.require_tag, Some("rare"));
     /// ```
-    pub fn modify(mut self) -> DropBuilder {
-        self.modify = true;
+    pub fn require_tag(mut self, tag: &'static str) -> DropBuilder {
+        self.require_tag = Some(tag);
         self
     }
 
@@ -208,13 +617,21 @@ impl DropBuilder {
     /// assert_eq!(drop.depth, 3);
     /// assert_eq!(drop.luck, 0.9);
     /// ```
-    pub fn build(&self) -> Drop {
+    pub fn build(&self) -> Drop<'static> {
         Drop {
             path: self.path,
             depth: self.depth,
             luck: self.luck,
             stack: self.stack.clone(),
+            stack_dist: self.stack_dist.clone(),
             modify: self.modify,
+            item: self.item,
+            requires: self.requires.clone(),
+            unique: self.unique,
+            guaranteed: self.guaranteed,
+            filter: self.filter,
+            rarity: self.rarity,
+            require_tag: self.require_tag,
         }
     }
 }
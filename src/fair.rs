@@ -0,0 +1,85 @@
+//! Module containing the fair bag used in Lootr.
+//!
+//! A [`FairBag`](crate::fair::FairBag) rolls items from a lootbag, lowering the chance of
+//! recently picked items so that streaks are smoothed out and pick frequencies stay even.
+//!
+//! Unlike pity timers, it does not guarantee rare drops: it only targets uniformity.
+//!
+
+use rand::Rng;
+
+use crate::{item::Item, pick, Lootr};
+
+/// Rolls items from a lootbag, with residual weights.
+///
+/// Each item starts with no residual weight, and recovers its own weight before every roll.
+/// Items are picked according to their positive residual weight, and a picked item loses
+/// the total weight of the bag, so it can only come back once the others caught up.
+///
+#[derive(Debug, Clone)]
+pub struct FairBag<'l, 'a> {
+    items: Vec<&'l Item<'a>>,
+    residuals: Vec<i64>,
+}
+
+impl<'l, 'a> FairBag<'l, 'a> {
+    /// Create a fair bag over all items in the current and nested branchs of the given lootbag.
+    ///
+    pub fn new(loot: &'l Lootr<'a>) -> Self {
+        let items = loot.all_item_refs();
+        let residuals = vec![0; items.len()];
+
+        Self { items, residuals }
+    }
+
+    /// Pick the next item, given a PRNG
+    ///
+    /// Returns `Some(Item)` or `None` if the bag holds no item with a weight
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::{fair::FairBag, item::Item, Lootr};
+    /// use rand::SeedableRng;
+    /// use rand_chacha::ChaCha20Rng;
+    ///
+    /// let loot = Lootr::from(vec![Item::a("Boots"), Item::a("Socks")]);
+    /// let mut bag = FairBag::new(&loot);
+    /// let rng = &mut ChaCha20Rng::seed_from_u64(123);
+    ///
+    /// let first = &bag.next(rng).unwrap().name;
+    /// let second = &bag.next(rng).unwrap().name;
+    ///
+    /// // with two items, the last picked one can not come out twice in a row
+    /// assert_ne!(first, second);
+    /// ```
+    pub fn next<R>(&mut self, rng: &mut R) -> Option<&'l Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let total: i64 = self.items.iter().map(|item| item.weight as i64).sum();
+
+        for (residual, item) in self.residuals.iter_mut().zip(&self.items) {
+            *residual += item.weight as i64;
+        }
+
+        let index = pick::weighted_u32(
+            rng,
+            self.residuals
+                .iter()
+                .map(|residual| (*residual).clamp(0, u32::MAX as i64) as u32),
+        )?;
+        self.residuals[index] -= total;
+
+        Some(self.items[index])
+    }
+
+    /// Return the current residual weight of each item, in the lootbag order.
+    ///
+    pub fn residuals(&self) -> impl Iterator<Item = (&'l Item<'a>, i64)> + '_ {
+        self.items
+            .iter()
+            .copied()
+            .zip(self.residuals.iter().copied())
+    }
+}
@@ -7,19 +7,162 @@
 //!
 //! Item [`Props`](crate::item::Props) can be queried directly with `has_prop()`, `get_prop()` and `set_prop()`
 //!
+//! Item strings are either borrowed, at no cost, or owned, see [`Item::owned`](crate::item::Item::owned).
+//!
 
 use std::{
-    collections::HashMap,
+    borrow::Cow,
+    collections::{hash_map, HashMap},
     fmt::{self, format, Display},
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+use crate::{
+    rarity::Rarity,
+    schema::{PropError, PropSchema},
 };
 
-/// Holds the item properties in an `HashMap<&str, &str>`.
+/// Holds the item properties, keys and values being borrowed or owned strings.
+///
+/// # Examples
+///
+/// ```
+/// use lootr::item::Props;
 ///
-pub type Props<'a> = HashMap<&'a str, &'a str>;
+/// let mut props = Props::from([("color", "black")]);
+/// props.insert("size", String::from("small"));
+///
+/// assert_eq!(props.get("color"), Some("black"));
+/// assert_eq!(props.get("size"), Some("small"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Props<'a>(
+    #[cfg_attr(feature = "serde", serde(borrow))] pub(crate) HashMap<Cow<'a, str>, Cow<'a, str>>,
+);
+
+impl<'a> Props<'a> {
+    /// Create an empty properties map.
+    ///
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    /// Create an empty properties map, with room for `capacity` properties.
+    ///
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(HashMap::with_capacity(capacity))
+    }
+
+    /// Set a property, returning the previous value if any.
+    ///
+    pub fn insert<K, V>(&mut self, key: K, value: V) -> Option<Cow<'a, str>>
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.0.insert(key.into(), value.into())
+    }
+
+    /// Return a property value.
+    ///
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|value| value.as_ref())
+    }
+
+    /// Check the existence of a property.
+    ///
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// Remove a property, returning its value if any.
+    ///
+    pub fn remove(&mut self, key: &str) -> Option<Cow<'a, str>> {
+        self.0.remove(key)
+    }
+
+    /// Return the number of properties.
+    ///
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if there is no property.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Return the number of properties the map can hold without reallocating.
+    ///
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Iterate over the properties, in arbitrary order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_ref(), value.as_ref()))
+    }
+}
+
+impl<'a, K, V> FromIterator<(K, V)> for Props<'a>
+where
+    K: Into<Cow<'a, str>>,
+    V: Into<Cow<'a, str>>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<'a, K, V> Extend<(K, V)> for Props<'a>
+where
+    K: Into<Cow<'a, str>>,
+    V: Into<Cow<'a, str>>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.0.extend(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into())),
+        );
+    }
+}
+
+impl<'a, K, V, const N: usize> From<[(K, V); N]> for Props<'a>
+where
+    K: Into<Cow<'a, str>>,
+    V: Into<Cow<'a, str>>,
+{
+    fn from(props: [(K, V); N]) -> Self {
+        props.into_iter().collect()
+    }
+}
+
+impl<'a> IntoIterator for Props<'a> {
+    type Item = (Cow<'a, str>, Cow<'a, str>);
+    type IntoIter = hash_map::IntoIter<Cow<'a, str>, Cow<'a, str>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
 
-/// Holds a modifier helper function.
+/// Holds a modifier helper function, or closure.
 ///
-pub type Modifier = fn(item: Item) -> Item;
+pub type Modifier<'a> = Box<dyn Fn(Item<'a>) -> Item<'a> + 'a>;
 
 /// Holds a Lootr Item.
 ///
@@ -28,25 +171,71 @@ pub type Modifier = fn(item: Item) -> Item;
 ///
 /// The easiest way to create an Item is to use [`Item::from`](crate::item::Item::from).
 ///
-#[derive(Debug, Clone)]
+/// Items compare and hash by all their fields, props are compared regardless of their insertion order.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Item<'a> {
     /// Holds the item name.
     ///
-    pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub name: Cow<'a, str>,
 
     /// Holds the item properties.
     ///
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub props: Option<Props<'a>>,
+
+    /// Holds the item rarity, if any.
+    ///
+    pub rarity: Option<Rarity>,
+
+    /// Holds the item tags.
+    ///
+    #[cfg_attr(feature = "serde", serde(borrow, default))]
+    pub tags: Vec<&'a str>,
+
+    /// Holds the item weight, biasing its pick against the other items of its branch.
+    /// Defaults to 1, an item with a zero weight is never picked.
+    ///
+    #[cfg_attr(feature = "serde", serde(default = "default_weight"))]
+    pub weight: u32,
+}
+
+#[cfg(feature = "serde")]
+fn default_weight() -> u32 {
+    1
+}
+
+impl<'a> Hash for Item<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.props
+            .as_ref()
+            .map(|props| {
+                let mut props: Vec<(&str, &str)> = props.iter().collect();
+                props.sort();
+                props
+            })
+            .hash(state);
+        self.rarity.hash(state);
+        self.tags.hash(state);
+        self.weight.hash(state);
+    }
 }
 
 impl<'a> Display for Item<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let props = self.props.clone().unwrap_or_default();
-        let props: Vec<String> = props
-            .iter()
+        let props: Vec<String> = self
+            .props_iter()
             .map(|(key, value)| format(format_args!("{}={}", key, value)))
             .collect::<_>();
-        write!(f, "{}{{{}}}", self.name, props.join(","))
+        write!(f, "{}{{{}}}", self.name, props.join(","))?;
+
+        match self.tags.is_empty() {
+            true => Ok(()),
+            false => write!(f, "[{}]", self.tags.join(",")),
+        }
     }
 }
 
@@ -61,7 +250,13 @@ impl<'a> Item<'a> {
     /// let hat = Item::a("hat");
     /// ```
     pub fn a(name: &'a str) -> Self {
-        Self { name, props: None }
+        Self {
+            name: Cow::Borrowed(name),
+            props: None,
+            rarity: None,
+            tags: vec![],
+            weight: 1,
+        }
     }
 
     /// Create an Item with just a name.
@@ -108,8 +303,44 @@ impl<'a> Item<'a> {
     /// ```
     pub fn from(name: &'a str, props: Props<'a>) -> Self {
         Item {
-            name,
+            name: Cow::Borrowed(name),
             props: Some(props),
+            rarity: None,
+            tags: vec![],
+            weight: 1,
+        }
+    }
+
+    /// Create an Item from owned strings, e.g. built at runtime or loaded from a file.
+    ///
+    /// The item owns its name and properties, so it does not borrow from its sources.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::{item::Item, Lootr};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut loot = Lootr::new();
+    ///
+    /// for n in 1..=3 {
+    ///     let props = HashMap::from([("bonus".to_string(), n.to_string())]);
+    ///     loot.add(Item::owned(format!("Sword+{n}"), props));
+    /// }
+    ///
+    /// assert_eq!(loot.items()[2].name, "Sword+3");
+    /// assert_eq!(loot.items()[2].get_prop("bonus"), Some("3"));
+    /// ```
+    pub fn owned(name: String, props: HashMap<String, String>) -> Self {
+        Item {
+            name: Cow::Owned(name),
+            props: match props.is_empty() {
+                true => None,
+                false => Some(props.into_iter().collect()),
+            },
+            rarity: None,
+            tags: vec![],
+            weight: 1,
         }
     }
 
@@ -133,15 +364,236 @@ impl<'a> Item<'a> {
     /// assert_eq!(cap.get_prop("color"), Some("black"));
     /// assert_eq!(cap.get_prop("size"), Some("small"));
     /// ```
-    pub fn extend(&self, name: &'a str, ext_props: Props<'a>) -> Self {
-        let mut new_props: HashMap<&str, &str> = HashMap::new();
-        new_props.extend(self.props.clone().unwrap_or_default().iter());
-        new_props.extend(ext_props.iter());
+    pub fn extend<N>(&self, name: N, ext_props: Props<'a>) -> Self
+    where
+        N: Into<Cow<'a, str>>,
+    {
+        let mut new_props = self.props.clone().unwrap_or_default();
+        new_props.extend(ext_props);
 
         Item {
-            name,
+            name: name.into(),
             props: Some(new_props),
+            rarity: self.rarity,
+            tags: self.tags.clone(),
+            weight: self.weight,
+        }
+    }
+
+    /// Create a copy of the item, owning its name and properties.
+    ///
+    /// Unlike `clone()`, which keeps borrowed strings borrowed, the name and every
+    /// property key and value are copied into owned strings. Tags stay borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let hat = Item::from("hat", Props::from([("color", "black")]));
+    /// let mut copy = hat.deep_clone();
+    ///
+    /// copy.set_prop("color", "white");
+    ///
+    /// assert_eq!(hat.get_prop("color"), Some("black"));
+    /// assert_eq!(copy.get_prop("color"), Some("white"));
+    /// ```
+    pub fn deep_clone(&self) -> Item<'a> {
+        let props = self.props.as_ref().map(|props| {
+            let mut copy = Props::with_capacity(props.len());
+            copy.extend(
+                props
+                    .0
+                    .iter()
+                    .map(|(k, v)| (Cow::Owned(k.to_string()), Cow::Owned(v.to_string()))),
+            );
+            copy
+        });
+
+        Item {
+            name: Cow::Owned(self.name.to_string()),
+            props,
+            rarity: self.rarity,
+            tags: self.tags.clone(),
+            weight: self.weight,
+        }
+    }
+
+    /// Create an Item by transforming one of its properties.
+    /// The closure receives the current value (if any) and returns the new one,
+    /// returning `None` removes the property.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let hat = Item::from("hat", Props::from([
+    ///     ("color", "black"),
+    ///     ("size", "small"),
+    /// ]));
+    ///
+    /// let dyed = hat.with_modified_prop("color", |color| match color {
+    ///     Some("black") => Some("white"),
+    ///     _ => Some("black"),
+    /// });
+    ///
+    /// assert_eq!(dyed.get_prop("color"), Some("white"));
+    /// assert_eq!(hat.get_prop("color"), Some("black"));
+    ///
+    /// let resized = hat.with_modified_prop("size", |size| size.map(|size| format!("very {size}")));
+    ///
+    /// assert_eq!(resized.get_prop("size"), Some("very small"));
+    /// ```
+    pub fn with_modified_prop<F, V>(&self, key: &'a str, f: F) -> Self
+    where
+        F: FnOnce(Option<&str>) -> Option<V>,
+        V: Into<Cow<'a, str>>,
+    {
+        let mut new_props = self.props.clone().unwrap_or_default();
+
+        match f(new_props.get(key)) {
+            None => new_props.remove(key),
+            Some(value) => new_props.insert(key, value),
+        };
+
+        Item {
+            props: Some(new_props),
+            ..self.clone()
+        }
+    }
+
+    /// Render the item with a template.
+    /// `{name}` is replaced by the item name, and `{prop:key}` by the `key` property.
+    /// Missing properties render empty, other text is kept as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let axe = Item::from("axe", Props::from([("attack", "12")]));
+    ///
+    /// assert_eq!(axe.format_with("{name} (ATK {prop:attack})"), "axe (ATK 12)");
+    /// ```
+    pub fn format_with(&self, template: &str) -> String {
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            output.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let end = match rest.find('}') {
+                None => break,
+                Some(end) => end,
+            };
+
+            match &rest[1..end] {
+                "name" => output.push_str(&self.name),
+                placeholder => match placeholder.strip_prefix("prop:") {
+                    Some(key) => output.push_str(self.get_prop(key).unwrap_or_default()),
+                    None => output.push_str(&rest[..=end]),
+                },
+            }
+
+            rest = &rest[end + 1..];
         }
+
+        output.push_str(rest);
+        output
+    }
+
+    /// Return an estimate of the item memory footprint, in bytes.
+    ///
+    /// It sums the name and properties bytes, and the properties map allocation.
+    /// Borrowed strings may be shared with other items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let hat = Item::from("hat", Props::from([("color", "black")]));
+    ///
+    /// assert!(hat.approx_heap_size() > Item::a("hat").approx_heap_size());
+    /// ```
+    pub fn approx_heap_size(&self) -> usize {
+        let props = match &self.props {
+            None => 0,
+            Some(props) => {
+                props.capacity() * std::mem::size_of::<(Cow<str>, Cow<str>)>()
+                    + props.iter().map(|(k, v)| k.len() + v.len()).sum::<usize>()
+            }
+        };
+
+        self.name.len() + props
+    }
+
+    /// Set the item rarity, returning the item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::{item::Item, rarity::Rarity};
+    ///
+    /// let crown = Item::a("crown").with_rarity(Rarity::Epic);
+    ///
+    /// assert_eq!(crown.rarity, Some(Rarity::Epic));
+    /// ```
+    pub fn with_rarity(mut self, rarity: Rarity) -> Self {
+        self.rarity = Some(rarity);
+        self
+    }
+
+    /// Set the item weight, returning the item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::Item;
+    ///
+    /// let dagger = Item::a("dagger").with_weight(10);
+    ///
+    /// assert_eq!(dagger.weight, 10);
+    /// ```
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Return the item tags.
+    ///
+    pub fn tags(&self) -> &[&'a str] {
+        &self.tags
+    }
+
+    /// Check if the item has the given tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::Item;
+    ///
+    /// let mut torch = Item::a("torch");
+    /// torch.add_tag("fire");
+    ///
+    /// assert_eq!(torch.has_tag("fire"), true);
+    /// assert_eq!(torch.has_tag("ice"), false);
+    /// ```
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(&tag)
+    }
+
+    /// Add a tag to the item.
+    /// If this tag already exist, nothing changes.
+    ///
+    pub fn add_tag(&mut self, tag: &'a str) -> &mut Self {
+        if !self.has_tag(tag) {
+            self.tags.push(tag);
+        }
+
+        self
     }
 
     /// Check the existence of an item property.
@@ -183,10 +635,162 @@ impl<'a> Item<'a> {
     pub fn get_prop(&self, key: &str) -> Option<&str> {
         match &self.props {
             None => None,
-            Some(props) => props.get(key).copied(),
+            Some(props) => props.get(key),
         }
     }
 
+    /// Return an item property, or the given default if this prop does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let hat = Item::from("hat", Props::from([("size", "small")]));
+    ///
+    /// assert_eq!(hat.get_prop_or("size", "medium"), "small");
+    /// assert_eq!(hat.get_prop_or("color", "black"), "black");
+    /// ```
+    pub fn get_prop_or<'b>(&'b self, key: &str, default: &'b str) -> &'b str {
+        self.get_prop(key).unwrap_or(default)
+    }
+
+    /// Iterate over the item properties, without cloning them.
+    /// Items without properties yield nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let hat = Item::from("hat", Props::from([("size", "small")]));
+    ///
+    /// assert_eq!(hat.props_iter().collect::<Vec<_>>(), vec![("size", "small")]);
+    /// ```
+    pub fn props_iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.props.iter().flat_map(|props| props.iter())
+    }
+
+    /// Return an item property, parsed as `T`.
+    /// If this prop does not exist, `None` is returned.
+    /// If it exists but can not be parsed, `Some(Err(_))` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let sword = Item::from("sword", Props::from([
+    ///     ("attack", "15"),
+    ///     ("edge", "sharp"),
+    /// ]));
+    ///
+    /// assert_eq!(sword.get_prop_as::<i32>("attack"), Some(Ok(15)));
+    /// assert!(sword.get_prop_as::<i32>("edge").unwrap().is_err());
+    /// assert!(sword.get_prop_as::<i32>("weight").is_none());
+    /// ```
+    pub fn get_prop_as<T: FromStr>(&self, key: &str) -> Option<Result<T, T::Err>> {
+        self.get_prop(key).map(str::parse)
+    }
+
+    /// Return an item property, parsed as `T`.
+    /// If this prop does not exist, or can not be parsed, `None` is returned.
+    /// Use [`Item::get_prop_as`](crate::item::Item::get_prop_as) to tell both cases apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let sword = Item::from("sword", Props::from([
+    ///     ("attack", "15"),
+    ///     ("edge", "sharp"),
+    /// ]));
+    ///
+    /// assert_eq!(sword.get_prop_parsed::<i32>("attack"), Some(15));
+    /// assert_eq!(sword.get_prop_parsed::<i32>("edge"), None);
+    /// ```
+    pub fn get_prop_parsed<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get_prop_as(key)?.ok()
+    }
+
+    /// Return an item property as a list, split on `sep`.
+    /// Values are trimmed and empty values are skipped.
+    /// If this prop does not exist, an empty list is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let cloak = Item::from("cloak", Props::from([
+    ///     ("resistances", "fire, ice,poison"),
+    /// ]));
+    ///
+    /// assert_eq!(cloak.get_prop_list("resistances", ','), vec!["fire", "ice", "poison"])
+    /// ```
+    pub fn get_prop_list(&self, key: &str, sep: char) -> Vec<&str> {
+        match self.get_prop(key) {
+            None => vec![],
+            Some(value) => value
+                .split(sep)
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Check if an item property list, split on `sep`, contains `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::item::{Item, Props};
+    ///
+    /// let cloak = Item::from("cloak", Props::from([
+    ///     ("resistances", "fire,ice,poison"),
+    /// ]));
+    ///
+    /// assert_eq!(cloak.has_prop_value("resistances", "ice", ','), true)
+    /// ```
+    pub fn has_prop_value(&self, key: &str, value: &str, sep: char) -> bool {
+        self.get_prop_list(key, sep).contains(&value)
+    }
+
+    /// Check the item properties against a schema.
+    /// Properties missing from the item, or from the schema, are not reported.
+    ///
+    /// Returns one error per mismatching property, in keys order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::{item::{Item, Props}, schema::{PropSchema, PropType}};
+    ///
+    /// let schema = PropSchema::new().prop("attack", PropType::Int);
+    /// let sword = Item::from("sword", Props::from([("attack", "sharp")]));
+    ///
+    /// assert_eq!(sword.validate_props(&schema).len(), 1);
+    /// ```
+    pub fn validate_props(&self, schema: &PropSchema) -> Vec<PropError> {
+        schema
+            .props
+            .iter()
+            .filter_map(|(key, expected)| {
+                let value = self.get_prop(key)?;
+
+                match expected.accepts(value) {
+                    true => None,
+                    false => Some(PropError {
+                        key: key.to_string(),
+                        expected: *expected,
+                        value: value.to_string(),
+                    }),
+                }
+            })
+            .collect()
+    }
+
     /// Set an item property.
     /// If this prop already exist, the value is replaced.
     ///
@@ -207,10 +811,7 @@ impl<'a> Item<'a> {
     /// assert_eq!(hat.get_prop("size"), Some("large"));
     /// ```
     pub fn set_prop<'b: 'a>(&mut self, key: &'b str, value: &'b str) -> &mut Self {
-        let mut new_props: HashMap<&str, &str> = HashMap::new();
-        new_props.extend(self.props.clone().unwrap_or_default().iter());
-        new_props.insert(key, value);
-        self.props = Some(new_props);
+        self.props.get_or_insert_with(Props::new).insert(key, value);
 
         self
     }
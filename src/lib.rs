@@ -1,320 +1,2571 @@
 #![doc = include_str!("../README.md")]
 
+pub mod cooldown;
 pub mod drops;
+pub mod fair;
 pub mod item;
+pub mod modifiers;
+mod pick;
+pub mod rarity;
+pub mod report;
+pub mod salvage;
+pub mod schema;
 mod tests;
 
 use ascii_tree::{
     write_tree,
     Tree::{Leaf, Node},
 };
-use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
-use std::{collections::BTreeMap, fmt};
+use std::{
+    borrow::Cow,
+    collections::{btree_map::Entry, BTreeMap, HashMap, HashSet},
+    fmt,
+    time::Instant,
+};
+
+use crate::{
+    cooldown::CooldownTracker,
+    drops::{Drop, DropSet},
+    item::{Item, Modifier, Props},
+    rarity::Rarity,
+    report::DropReport,
+};
+
+pub const ROOT: Option<&str> = None;
+const SEPARATOR: char = '/';
+const MAX_LOOT_ROUNDS: usize = 100;
+
+/// Holds a lootbag: items, and named sub branchs.
+///
+/// With the `serde` feature, lootbags can be serialized. Modifiers are not serialized.
+///
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Lootr<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    items: Vec<Item<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    branchs: BTreeMap<Cow<'a, str>, Lootr<'a>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    modifiers: Vec<(&'a str, Modifier<'a>)>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    default_props: Props<'a>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    threshold_dist: ThresholdDist,
+    #[cfg_attr(feature = "serde", serde(default))]
+    sorted_modifiers: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    merge_on_add: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    rng: Option<ChaCha20Rng>,
+}
+
+/// Holds the options used to render a lootbag tree.
+///
+/// See [`Lootr::render()`](crate::Lootr::render)
+///
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    /// If true, items are rendered with their properties.
+    ///
+    pub show_props: bool,
+
+    /// If true, branchs are rendered with their items count (including any sublevel).
+    ///
+    pub show_counts: bool,
+
+    /// Holds the maximum depth of rendered branchs, if any.
+    ///
+    pub max_depth: Option<usize>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            show_props: true,
+            show_counts: false,
+            max_depth: None,
+        }
+    }
+}
+
+/// Holds what is needed to replay a loot.
+///
+/// See [`Lootr::loot_seeded_with_meta()`](crate::Lootr::loot_seeded_with_meta)
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LootMeta {
+    /// Holds the seed the loot was rolled with.
+    ///
+    pub seed: u64,
+
+    /// Holds, for each looted item, the index of the drop that yield it.
+    ///
+    pub drop_indices: Vec<usize>,
+}
+
+/// Holds a structured log of a loot session, to replay or debug it.
+///
+/// With the `serde` feature, logs can be serialized, and deserialized from `'static` data.
+///
+/// See [`Lootr::loot_logged()`](crate::Lootr::loot_logged)
+///
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(deserialize = "'de: 'static"))
+)]
+pub struct LootLog<'a> {
+    /// Holds the seed the loot was rolled with.
+    ///
+    pub seed: u64,
+
+    /// Holds the drops the loot was rolled against.
+    ///
+    pub drops: Vec<Drop<'a>>,
+
+    /// Holds an entry for each looted item, in the rewards order.
+    ///
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub entries: Vec<LootLogEntry<'a>>,
+}
+
+/// Holds a looted item in a [`LootLog`](crate::LootLog).
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LootLogEntry<'a> {
+    /// Holds the index of the drop that yield the item.
+    ///
+    pub drop_index: usize,
+
+    /// Holds the path of the branch the item was picked from, empty for the root.
+    ///
+    pub path: String,
+
+    /// Holds the looted item name.
+    ///
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub name: Cow<'a, str>,
+}
+
+/// Holds the work done by a roll.
+///
+/// See [`Lootr::roll_profiled()`](crate::Lootr::roll_profiled)
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RollProfile {
+    /// Holds the number of visited branchs, including the rolled one.
+    ///
+    pub branches_visited: usize,
+
+    /// Holds the number of items eligible at the visited branchs.
+    ///
+    pub items_considered: usize,
+
+    /// Holds the number of picked items rejected by the luck threshold.
+    ///
+    pub rejections: usize,
+}
+
+/// Holds the distribution of the draws checked against roll thresholds.
+///
+/// An item passes a threshold when the draw is lower than the threshold,
+/// so distributions leaning toward 0 favor success.
+///
+/// See [`Lootr::set_threshold_dist()`](crate::Lootr::set_threshold_dist)
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThresholdDist {
+    /// Uniform draws, the default.
+    ///
+    #[default]
+    Uniform,
+
+    /// Beta(a, b) draws, e.g. `Beta(1.0, 3.0)` favors success. Both parameters must be positive and finite,
+    /// see [`Lootr::set_threshold_dist()`](crate::Lootr::set_threshold_dist).
+    ///
+    Beta(f32, f32),
+}
+
+impl ThresholdDist {
+    /// Check the distribution parameters
+    ///
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            ThresholdDist::Uniform => true,
+            ThresholdDist::Beta(a, b) => a > 0.0 && b > 0.0 && a.is_finite() && b.is_finite(),
+        }
+    }
+
+    /// Draw a value to check against a threshold, given a PRNG
+    ///
+    /// Invalid distributions fall back to uniform draws.
+    ///
+    pub fn sample<R>(&self, rng: &mut R) -> f32
+    where
+        R: Rng + ?Sized,
+    {
+        match *self {
+            ThresholdDist::Beta(a, b) if self.is_valid() => pick::beta(rng, a, b),
+            _ => pick::unit(rng),
+        }
+    }
+}
+
+/// Holds the errors returned by fallible Lootr methods.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LootrError {
+    /// A path segment does not match any branch, holds the offending segment.
+    PathNotFound(String),
+
+    /// A threshold distribution has invalid parameters, holds the distribution.
+    InvalidThresholdDist(String),
+
+    /// A stack distribution has a negative or non finite weight, holds the offending pair.
+    InvalidStackDist(String),
+}
+
+impl fmt::Display for LootrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LootrError::PathNotFound(segment) => write!(f, "this branch does not exist: {segment}"),
+            LootrError::InvalidThresholdDist(dist) => {
+                write!(f, "this threshold distribution is invalid: {dist}")
+            }
+            LootrError::InvalidStackDist(pair) => {
+                write!(f, "this stack distribution weight is invalid: {pair}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LootrError {}
+
+impl<'a> fmt::Display for Lootr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_tree(f, &self.fmt_node("ROOT", &RenderOptions::default(), 0))
+    }
+}
+
+impl<'a> Default for Lootr<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Lootr<'a> {
+    /// Create a new lootbag
+    ///
+    pub fn new() -> Self {
+        Self::from(vec![])
+    }
+
+    /// Create a new lootbag, filled by the given closure
+    ///
+    pub fn build<F>(f: F) -> Self
+    where
+        F: FnOnce(&mut Lootr<'a>),
+    {
+        let mut loot = Self::new();
+        f(&mut loot);
+        loot
+    }
+
+    /// Create a new lootbag from given items
+    ///
+    pub fn from(items: Vec<Item<'a>>) -> Self {
+        Self {
+            items,
+            branchs: BTreeMap::new(),
+            modifiers: vec![],
+            default_props: Props::new(),
+            threshold_dist: ThresholdDist::Uniform,
+            sorted_modifiers: false,
+            merge_on_add: false,
+            rng: None,
+        }
+    }
+
+    /// Create a new lootbag, holding a PRNG seeded from `seed`
+    ///
+    /// `roll_next()`, `roll_any_next()` and `loot_next()` advance this PRNG,
+    /// so successive calls yield the same sequence for the same seed.
+    /// `roll()`, `roll_any()` and `loot()` keep using a fresh PRNG.
+    ///
+    pub fn new_seeded(seed: u64) -> Self {
+        Self {
+            rng: Some(ChaCha20Rng::seed_from_u64(seed)),
+            ..Self::new()
+        }
+    }
+
+    /// Render this lootbag as a Graphviz DOT graph
+    ///
+    /// Branchs and items are nodes, linked to their parent branch. Item nodes are labelled with their properties.
+    ///
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("digraph lootr {\n");
+        self.dot_node("ROOT", &mut 0, &mut output);
+        output.push_str("}\n");
+
+        output
+    }
+
+    /// Render this lootbag as a tree, with the given options
+    ///
+    /// The `Display` implementation uses the default options.
+    ///
+    pub fn render(&self, opts: RenderOptions) -> String {
+        let mut output = String::new();
+        write_tree(&mut output, &self.fmt_node("ROOT", &opts, 0))
+            .expect("writing to a String should not fail");
+
+        output
+    }
+
+    /// Return this lootbag branchs
+    ///
+    pub fn branchs(&self) -> &BTreeMap<Cow<'a, str>, Lootr<'a>> {
+        &self.branchs
+    }
+
+    /// Return this lootbag items (at this level)
+    ///
+    pub fn items(&self) -> &Vec<Item<'a>> {
+        &self.items
+    }
+
+    /// Return this lootbag items count (at this level)
+    ///
+    pub fn self_count(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Return the items count of the branch at the given path (at this level)
+    ///
+    /// Returns `None` if the branch does not exist
+    ///
+    pub fn self_count_in(&self, path: &str) -> Option<usize> {
+        self.branch(path).map(|branch| branch.self_count())
+    }
+
+    /// Return this lootbag items count (including any sublevel)
+    ///
+    pub fn all_count(&self) -> usize {
+        self.items.len()
+            + self
+                .branchs
+                .values()
+                .map(|branch| branch.all_count())
+                .sum::<usize>()
+    }
+
+    /// Return an estimate of this lootbag memory footprint, in bytes (including any sublevel)
+    ///
+    /// See [`Item::approx_heap_size()`](crate::item::Item::approx_heap_size)
+    ///
+    pub fn approx_heap_size(&self) -> usize {
+        let items = self.items.capacity() * std::mem::size_of::<Item>()
+            + self
+                .items
+                .iter()
+                .map(|item| item.approx_heap_size())
+                .sum::<usize>();
+
+        let modifiers = self.modifiers.capacity() * std::mem::size_of::<(&str, Modifier)>();
+
+        let branchs: usize = self
+            .branchs
+            .iter()
+            .map(|(name, branch)| {
+                name.len() + std::mem::size_of::<(&str, Lootr)>() + branch.approx_heap_size()
+            })
+            .sum();
+
+        items + modifiers + branchs
+    }
+
+    /// Return the items count of the branch at `prefix` and of all its sublevels
+    ///
+    /// Whole path segments are matched: `weapons` does not count the items of `weapons_old`.
+    /// An empty prefix counts all items.
+    ///
+    pub fn count_under_prefix(&self, prefix: &str) -> usize {
+        let prefix = prefix.trim_matches(SEPARATOR);
+        let nested = format!("{prefix}{SEPARATOR}");
+        let mut count = 0;
+
+        self.walk("", &mut |path, branch| {
+            if prefix.is_empty() || path == prefix || path.starts_with(&nested) {
+                count += branch.self_count();
+            }
+        });
+
+        count
+    }
+
+    /// Return the number of branchs exactly `depth` levels below this one
+    ///
+    pub fn branches_at_depth(&self, depth: i16) -> usize {
+        match depth {
+            i16::MIN..=0 => 0,
+            1 => self.branchs.len(),
+            _ => self
+                .branchs
+                .values()
+                .map(|b| b.branches_at_depth(depth - 1))
+                .sum(),
+        }
+    }
+
+    /// Add an item at this level
+    ///
+    /// Returns the current lootbag
+    ///
+    pub fn add(&mut self, item: Item<'a>) -> &mut Self {
+        if self.merge_on_add {
+            if let Some(existing) = self.items.iter_mut().find(|i| i.name == item.name) {
+                let props = item.props.unwrap_or_default();
+                existing.props.get_or_insert_with(Props::new).extend(props);
+                return self;
+            }
+        }
+
+        self.items.push(item);
+
+        self
+    }
+
+    /// Add an item in the branch named by its `by` property, creating the branch if needed
+    ///
+    /// Items without this property are added at this level.
+    ///
+    /// Returns the current lootbag
+    ///
+    pub fn add_routed(&mut self, item: Item<'a>, by: &str) -> &mut Self {
+        let route = item
+            .props
+            .as_ref()
+            .and_then(|props| props.0.get(by).cloned());
+
+        match route {
+            None => self.add(item),
+            Some(name) => {
+                self.branchs.entry(name).or_default().add(item);
+                self
+            }
+        }
+    }
+
+    /// Merge added items into an existing item with the same name, at this level
+    ///
+    /// Properties of the added item override the existing ones.
+    /// By default, items with the same name are kept side by side.
+    ///
+    pub fn merge_on_add(&mut self, merge: bool) -> &mut Self {
+        self.merge_on_add = merge;
+        self
+    }
+
+    /// Set a default property, given to looted items of this branch and its sub branchs
+    ///
+    /// Item props override branch defaults, and defaults of a sub branch override the ones of its parents.
+    /// Defaults are applied at loot time, before modifiers.
+    ///
+    /// Returns the current lootbag
+    ///
+    pub fn set_default_prop(&mut self, key: &'a str, value: &'a str) -> &mut Self {
+        self.default_props.insert(key, value);
+        self
+    }
+
+    /// Return the default properties of this branch
+    ///
+    pub fn default_props(&self) -> &Props<'a> {
+        &self.default_props
+    }
+
+    /// Set the distribution of the draws checked against roll thresholds
+    ///
+    /// Like the threshold, the distribution is read from the lootbag a roll is made on, and used
+    /// for all its sub branchs, whenever they were added.
+    ///
+    /// Returns the current lootbag, or a [`LootrError::InvalidThresholdDist`](crate::LootrError::InvalidThresholdDist)
+    /// if the parameters are not positive and finite
+    ///
+    pub fn set_threshold_dist(&mut self, dist: ThresholdDist) -> Result<&mut Self, LootrError> {
+        if !dist.is_valid() {
+            return Err(LootrError::InvalidThresholdDist(format!("{dist:?}")));
+        }
+
+        self.threshold_dist = dist;
+
+        Ok(self)
+    }
+
+    /// Add an item in the given branch
+    ///
+    /// Missing branchs along the path are created, see [`Lootr::try_add_in()`](crate::Lootr::try_add_in)
+    /// to only add in existing branchs.
+    ///
+    /// Returns the current lootbag
+    ///
+    pub fn add_in(&mut self, item: Item<'a>, path: &'a str) -> &mut Self {
+        self.ensure_branch(path).add(item);
+        self
+    }
+
+    /// Returns the branch at the given path, creating any missing branch along the path.
+    /// Existing branchs are kept as is.
+    ///
+    pub fn ensure_branch(&mut self, path: &'a str) -> &mut Lootr<'a> {
+        path.trim_matches(SEPARATOR)
+            .split(SEPARATOR)
+            .filter(|segment| !segment.is_empty())
+            .fold(self, |acc, segment| {
+                acc.branchs.entry(Cow::Borrowed(segment)).or_default()
+            })
+    }
+
+    /// Add an item in the given branch
+    ///
+    /// Returns the current lootbag, or a [`LootrError::PathNotFound`](crate::LootrError::PathNotFound)
+    ///
+    pub fn try_add_in(&mut self, item: Item<'a>, path: &str) -> Result<&mut Self, LootrError> {
+        self.try_branch_mut(path)?.add(item);
+
+        Ok(self)
+    }
+
+    /// Returns the branch at the given path.
+    /// If the branch does not exit yet, `None` is returned
+    ///
+    pub fn branch_mut(&mut self, path: &str) -> Option<&mut Lootr<'a>> {
+        self.try_branch_mut(path).ok()
+    }
+
+    /// Returns the branch at the given path.
+    /// If the branch does not exit yet, `None` is returned
+    ///
+    pub fn branch(&self, path: &str) -> Option<&Lootr<'a>> {
+        self.try_branch(path).ok()
+    }
+
+    /// Check if a branch exists at the given path.
+    ///
+    pub fn has_branch(&self, path: &str) -> bool {
+        self.branch(path).is_some()
+    }
+
+    /// Return the full path of every nested branch, the current level excluded
+    ///
+    /// Paths are sorted depth-first, sub branchs by name, e.g. `equipment`, `equipment/leather`, `weapons`.
+    ///
+    pub fn all_paths(&self) -> Vec<String> {
+        let mut paths = vec![];
+
+        self.walk("", &mut |path, _| {
+            if !path.is_empty() {
+                paths.push(path.to_string());
+            }
+        });
+
+        paths
+    }
+
+    /// Returns the branch at the given path.
+    ///
+    /// Returns the branch, or a [`LootrError::PathNotFound`](crate::LootrError::PathNotFound) holding the first missing segment
+    ///
+    pub fn try_branch_mut(&mut self, path: &str) -> Result<&mut Lootr<'a>, LootrError> {
+        let cname = path.trim_matches(SEPARATOR);
+
+        // simple case
+        if self.branchs.contains_key(cname) {
+            return Ok(self.branchs.get_mut(cname).unwrap());
+        }
+
+        // segmented path
+        cname.split(SEPARATOR).try_fold(self, |acc, s| {
+            acc.branchs
+                .get_mut(s)
+                .ok_or_else(|| LootrError::PathNotFound(s.to_string()))
+        })
+    }
+
+    /// Returns the branch at the given path.
+    ///
+    /// Returns the branch, or a [`LootrError::PathNotFound`](crate::LootrError::PathNotFound) holding the first missing segment
+    ///
+    pub fn try_branch(&self, path: &str) -> Result<&Lootr<'a>, LootrError> {
+        let cname = path.trim_matches(SEPARATOR);
+
+        // simple case
+        if let Some(branch) = self.branchs.get(cname) {
+            return Ok(branch);
+        }
+
+        // segmented path
+        cname.split(SEPARATOR).try_fold(self, |acc, s| {
+            acc.branchs
+                .get(s)
+                .ok_or_else(|| LootrError::PathNotFound(s.to_string()))
+        })
+    }
+
+    /// Serialize the lootbag in a compact binary format.
+    /// Modifiers are not serialized.
+    ///
+    /// Returns the bytes, or a bincode error
+    ///
+    #[cfg(feature = "bincode")]
+    pub fn to_bincode(&self) -> Result<Vec<u8>, bincode::Error> {
+        bincode::serialize(self)
+    }
+
+    /// Deserialize a lootbag from bytes produced by [`Lootr::to_bincode()`](crate::Lootr::to_bincode).
+    /// The lootbag borrows its strings from the given bytes, and has no modifiers.
+    ///
+    /// Returns the lootbag, or a bincode error
+    ///
+    #[cfg(feature = "bincode")]
+    pub fn from_bincode(bytes: &'a [u8]) -> Result<Self, bincode::Error> {
+        bincode::deserialize(bytes)
+    }
+
+    /// Resolve the branch at the given path, segment by segment.
+    ///
+    /// Returns the branch, or `None`, and the longest matched path prefix
+    ///
+    pub fn resolve(&self, path: &str) -> (Option<&Lootr<'a>>, String) {
+        let mut branch = self;
+        let mut matched: Vec<&str> = vec![];
+
+        for segment in path.trim_matches(SEPARATOR).split(SEPARATOR) {
+            match branch.branchs.get(segment) {
+                None => return (None, matched.join(&SEPARATOR.to_string())),
+                Some(b) => {
+                    branch = b;
+                    matched.push(segment);
+                }
+            }
+        }
+
+        (Some(branch), matched.join(&SEPARATOR.to_string()))
+    }
+
+    /// Add a branch, return self (the owner)
+    ///
+    /// The branch name can be borrowed or owned, e.g. built at runtime.
+    ///
+    pub fn add_branch<P>(&mut self, path: P, branch: Lootr<'a>) -> &mut Self
+    where
+        P: Into<Cow<'a, str>>,
+    {
+        self.branchs.insert(path.into(), branch);
+        self
+    }
+
+    /// Detach the branch at the given path
+    /// Only the last path segment is removed from its parent branch.
+    ///
+    /// Returns the removed branch, or `None` if the path does not exist
+    ///
+    pub fn remove_branch(&mut self, path: &str) -> Option<Lootr<'a>> {
+        let cname = path.trim_matches(SEPARATOR);
+
+        match cname.rsplit_once(SEPARATOR) {
+            None => self.branchs.remove(cname),
+            Some((parent, leaf)) => self.branch_mut(parent)?.branchs.remove(leaf),
+        }
+    }
+
+    /// Remove every item matching the predicate, in the current branch only
+    ///
+    /// Returns the removed items
+    ///
+    pub fn remove_matching<F>(&mut self, pred: F) -> Vec<Item<'a>>
+    where
+        F: Fn(&Item) -> bool,
+    {
+        let (removed, kept) = std::mem::take(&mut self.items)
+            .into_iter()
+            .partition(|item| pred(item));
+
+        self.items = kept;
+
+        removed
+    }
+
+    /// Set a property on every item, in the current and nested branchs
+    /// If an item already has this prop, the value is replaced.
+    ///
+    /// Returns the current lootbag
+    ///
+    pub fn set_prop_on_all(&mut self, key: &'a str, value: &'a str) -> &mut Self {
+        for item in self.items.iter_mut() {
+            item.set_prop(key, value);
+        }
+
+        for b in self.branchs.values_mut() {
+            b.set_prop_on_all(key, value);
+        }
+
+        self
+    }
+
+    /// Remove duplicated items, in the current and nested branchs
+    /// The first occurrence of an item is kept at each level.
+    ///
+    /// Returns the current lootbag
+    ///
+    pub fn dedup(&mut self) -> &mut Self {
+        let mut seen = HashSet::new();
+
+        self.items.retain(|item| seen.insert(item.clone()));
+
+        for b in self.branchs.values_mut() {
+            b.dedup();
+        }
+
+        self
+    }
+
+    /// Remove every item matching the predicate, in the current and nested branchs
+    ///
+    /// Returns the number of removed items
+    ///
+    pub fn prune_items_matching<F>(&mut self, pred: F) -> usize
+    where
+        F: Fn(&Item) -> bool,
+    {
+        self.prune_where(&pred)
+    }
+
+    /// Remove every item with the given name, in the current and nested branchs
+    ///
+    /// Returns the number of removed items
+    ///
+    pub fn remove_item_everywhere(&mut self, name: &str) -> usize {
+        self.prune_where(&|item| item.name == name)
+    }
+
+    /// Randomly remove items from every branch holding more than `max_per_branch` items, given a PRNG
+    ///
+    /// Returns the number of removed items
+    ///
+    pub fn trim<R>(&mut self, max_per_branch: usize, rng: &mut R) -> usize
+    where
+        R: Rng + ?Sized,
+    {
+        let mut removed = 0;
+
+        while self.items.len() > max_per_branch {
+            self.items.remove(pick::index(rng, self.items.len()));
+            removed += 1;
+        }
+
+        for b in self.branchs.values_mut() {
+            removed += b.trim(max_per_branch, rng);
+        }
+
+        removed
+    }
+
+    fn prune_where<F>(&mut self, pred: &F) -> usize
+    where
+        F: Fn(&Item) -> bool,
+    {
+        let before = self.items.len();
+        self.items.retain(|item| !pred(item));
+
+        let nested: usize = self.branchs.values_mut().map(|b| b.prune_where(pred)).sum();
+
+        before - self.items.len() + nested
+    }
+
+    /// Replace this lootbag items, branchs, modifiers and default props by the given lootbag ones, in one operation
+    ///
+    /// Default props describe the contents, so they are replaced along with them.
+    /// This lootbag settings are kept: its held PRNG, threshold distribution,
+    /// modifiers order and merge behavior.
+    ///
+    /// Returns the current lootbag
+    ///
+    pub fn replace_contents(&mut self, new: Lootr<'a>) -> &mut Self {
+        self.items = new.items;
+        self.branchs = new.branchs;
+        self.modifiers = new.modifiers;
+        self.default_props = new.default_props;
+        self
+    }
+
+    /// Merge the given lootbag items, branchs and modifiers into this one
+    ///
+    /// Branchs existing on both sides are merged recursively.
+    ///
+    /// Returns the current lootbag
+    ///
+    pub fn merge(&mut self, other: Lootr<'a>) -> &mut Self {
+        self.items.extend(other.items);
+        self.modifiers.extend(other.modifiers);
+        self.default_props.extend(other.default_props);
+
+        for (name, branch) in other.branchs {
+            match self.branchs.entry(name) {
+                Entry::Vacant(entry) => {
+                    entry.insert(branch);
+                }
+                Entry::Occupied(mut entry) => {
+                    entry.get_mut().merge(branch);
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Merge the given lootbag into the branch at the given path
+    ///
+    /// Missing branchs along the path are created.
+    ///
+    /// Returns the current lootbag
+    ///
+    pub fn merge_at(&mut self, path: &'a str, other: Lootr<'a>) -> &mut Self {
+        self.ensure_branch(path).merge(other);
+        self
+    }
+
+    /// Return all items in the current and nested branchs
+    ///
+    pub fn all_items(&self) -> Vec<Item<'a>> {
+        let mut bag = self.items.clone();
+
+        for b in self.branchs.values() {
+            bag.append(&mut b.all_items());
+        }
+
+        bag
+    }
+
+    /// Iterate over all items in the current and nested branchs, without cloning them
+    ///
+    /// Each item comes with its full path, e.g. `equipment/leather/Jacket`.
+    ///
+    pub fn iter_all(&self) -> impl Iterator<Item = (String, &Item<'a>)> {
+        self.iter_detailed().map(|(_, path, item)| {
+            let full = match path.as_str() {
+                "" => item.name.to_string(),
+                _ => format!("{path}{SEPARATOR}{}", item.name),
+            };
+            (full, item)
+        })
+    }
+
+    /// Iterate over all items in the current and nested branchs, without cloning them
+    ///
+    /// Each item comes with the depth and path of its branch, e.g. `(2, "equipment/leather", Jacket)`.
+    /// Items of the current level have a depth of 0 and an empty path.
+    ///
+    pub fn iter_detailed(&self) -> impl Iterator<Item = (i16, String, &Item<'a>)> {
+        let mut bag = vec![];
+
+        self.walk("", &mut |path, branch| {
+            let depth = match path {
+                "" => 0,
+                _ => path.split(SEPARATOR).count() as i16,
+            };
+            bag.extend(
+                branch
+                    .items
+                    .iter()
+                    .map(|item| (depth, path.to_string(), item)),
+            );
+        });
+
+        bag.into_iter()
+    }
+
+    /// Return all items matching the predicate, in the current and nested branchs, without cloning them
+    ///
+    pub fn find<F>(&self, pred: F) -> Vec<&Item<'a>>
+    where
+        F: Fn(&Item) -> bool,
+    {
+        self.all_item_refs()
+            .into_iter()
+            .filter(|item| pred(item))
+            .collect()
+    }
+
+    /// Return all items having the given property value, in the current and nested branchs, without cloning them
+    ///
+    pub fn find_by_prop(&self, key: &str, value: &str) -> Vec<&Item<'a>> {
+        self.find(|item| item.get_prop(key) == Some(value))
+    }
+
+    /// Check if an item with the given name exists, in the current and nested branchs
+    ///
+    pub fn contains_item(&self, name: &str) -> bool {
+        self.all_item_refs().iter().any(|item| item.name == name)
+    }
+
+    /// Return unique items (by name and properties) in the current and nested branchs
+    ///
+    pub fn distinct_items(&self) -> Vec<Item<'a>> {
+        let mut seen = HashSet::new();
+
+        self.all_item_refs()
+            .into_iter()
+            .filter(|item| seen.insert(*item))
+            .cloned()
+            .collect()
+    }
+
+    /// Add a modifier
+    ///
+    /// Modifiers added to a branch only apply to items looted from that branch, or its sub branchs.
+    /// Any function or closure taking and returning an Item can be used.
+    ///
+    pub fn add_modifier<F>(&mut self, modifier: F) -> &mut Self
+    where
+        F: Fn(Item<'a>) -> Item<'a> + 'a,
+    {
+        self.add_named_modifier("", modifier)
+    }
+
+    /// Add a named modifier
+    ///
+    /// Names are used to order modifiers when [`sort_modifiers()`](crate::Lootr::sort_modifiers) is enabled.
+    ///
+    pub fn add_named_modifier<F>(&mut self, name: &'a str, modifier: F) -> &mut Self
+    where
+        F: Fn(Item<'a>) -> Item<'a> + 'a,
+    {
+        self.modifiers.push((name, Box::new(modifier)));
+        self
+    }
+
+    /// Select modifiers by name order rather than registration order
+    ///
+    /// This keeps seeded loots reproducible when modifiers may be registered
+    /// in a different order, e.g. across data reloads.
+    ///
+    pub fn sort_modifiers(&mut self, sorted: bool) -> &mut Self {
+        self.sorted_modifiers = sorted;
+        self
+    }
+
+    /// Pick a random item from the specified branch
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+    ) -> Option<&Item<'a>> {
+        self.roll_seeded(
+            catalog_path,
+            nesting,
+            threshold,
+            &mut ChaCha20Rng::from_entropy(),
+        )
+    }
+
+    /// Pick a random item from the specified branch, with the PRNG held by this lootbag
+    ///
+    /// See [`Lootr::new_seeded()`](crate::Lootr::new_seeded). The item is copied,
+    /// as the lootbag is borrowed mutably to advance its PRNG.
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_next(
+        &mut self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+    ) -> Option<Item<'a>> {
+        self.with_rng(|bag, rng| bag.roll_owned_seeded(catalog_path, nesting, threshold, rng))
+    }
+
+    /// Pick a random item from the specified branch, given a PRNG
+    ///
+    /// The same PRNG state always picks the same item, whatever the `rand` version.
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_seeded<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+        rng: &mut R,
+    ) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.branch_at(catalog_path);
+
+        branch.random_pick(nesting, threshold, self.threshold_dist, rng, &|_| true)
+    }
+
+    /// Pick a random item from the specified branch, and return a copy of it
+    ///
+    /// The returned item does not borrow the lootbag, e.g. to send it to another thread.
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_owned(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+    ) -> Option<Item<'a>> {
+        self.roll(catalog_path, nesting, threshold).cloned()
+    }
+
+    /// Pick a random item from the specified branch, given a PRNG, and return a copy of it
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_owned_seeded<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+        rng: &mut R,
+    ) -> Option<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        self.roll_seeded(catalog_path, nesting, threshold, rng)
+            .cloned()
+    }
+
+    /// Pick a random item from an already resolved branch, given a PRNG
+    ///
+    /// Avoids resolving the branch path again, e.g. in tight loops.
+    /// The branch is rolled on its own, use `Lootr::roll_in(branch, ...)`.
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_in<'b, R>(
+        branch: &'b Lootr<'a>,
+        nesting: i16,
+        threshold: f32,
+        rng: &mut R,
+    ) -> Option<&'b Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        branch.random_pick(nesting, threshold, branch.threshold_dist, rng, &|_| true)
+    }
+
+    /// Pick random items from the specified branch until one matches `stop`, given a PRNG
+    ///
+    /// At most `max_attempts` rolls are made, rolls yielding nothing count as attempts.
+    ///
+    /// Returns every rolled item, and whether the stop condition was met
+    ///
+    pub fn roll_until<F, R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+        stop: F,
+        max_attempts: usize,
+        rng: &mut R,
+    ) -> (Vec<Item<'a>>, bool)
+    where
+        F: Fn(&Item) -> bool,
+        R: Rng + ?Sized,
+    {
+        let mut rolled = vec![];
+
+        for _ in 0..max_attempts {
+            if let Some(item) = self.roll_seeded(catalog_path, nesting, threshold, rng) {
+                rolled.push(item.clone());
+
+                if stop(item) {
+                    return (rolled, true);
+                }
+            }
+        }
+
+        (rolled, false)
+    }
+
+    /// Pick a random item from the specified branch, given a PRNG, and count the work done
+    ///
+    /// Returns `Some(Item)` or `None`, and a [`RollProfile`](crate::RollProfile)
+    ///
+    pub fn roll_profiled<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+        rng: &mut R,
+    ) -> (Option<&Item<'a>>, RollProfile)
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.branch_at(catalog_path);
+
+        let mut profile = RollProfile::default();
+        if threshold <= 0.0 {
+            return (None, profile);
+        }
+
+        let picked = branch
+            .random_pick_traced(
+                nesting,
+                threshold,
+                self.threshold_dist,
+                rng,
+                &|_| true,
+                &mut profile,
+            )
+            .map(|(_, item)| item);
+
+        (picked, profile)
+    }
+
+    /// Return the candidates a roll would choose from, given a PRNG
+    ///
+    /// With the same PRNG state, `roll_seeded()` picks its item among these candidates.
+    ///
+    /// Returns a vec of candidate items, possibly empty
+    ///
+    pub fn candidates<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+        rng: &mut R,
+    ) -> Vec<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.branch_at(catalog_path);
+
+        if threshold <= 0.0 {
+            return vec![];
+        }
+
+        branch
+            .candidate_bag(
+                nesting,
+                threshold,
+                self.threshold_dist,
+                rng,
+                &|_| true,
+                &mut RollProfile::default(),
+            )
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect()
+    }
+
+    /// Pick a random item from the sub branchs of the specified branch, given a PRNG
+    ///
+    /// Items at the specified branch level are never picked, `nesting` should be at least 1.
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_children_only<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+        rng: &mut R,
+    ) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.branch_at(catalog_path);
+
+        let profile = &mut RollProfile::default();
+        let bag = branch.branch_candidates(
+            nesting,
+            threshold,
+            self.threshold_dist,
+            rng,
+            &|_| true,
+            profile,
+        );
+
+        pick::choose(rng, &bag).map(|(_, item)| *item)
+    }
+
+    /// Pick a random item anywhere in the specified branch, given a PRNG
+    ///
+    /// Unlike `roll()`, thresholds do not decay with depth: every item of the subtree has the same chance,
+    /// only biased by its weight.
+    ///
+    /// Returns `Some(Item)` or `None` if the branch holds no item
+    ///
+    pub fn roll_uniform<R>(&self, catalog_path: Option<&'a str>, rng: &mut R) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.branch_at(catalog_path);
+
+        let items = branch.all_item_refs();
+        let index = pick::weighted_u32(rng, items.iter().map(|item| item.weight))?;
+
+        Some(items[index])
+    }
+
+    /// Pick a random item from the specified branch, given a PRNG
+    ///
+    /// If `fallback_to_parent` is true and nothing is picked, retries one level up.
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_with_fallback<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+        fallback_to_parent: bool,
+        rng: &mut R,
+    ) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let picked = self.roll_seeded(catalog_path, nesting, threshold, rng);
+
+        match (picked, catalog_path) {
+            (None, Some(path)) if fallback_to_parent => {
+                let parent = path.trim_matches(SEPARATOR).rsplit_once(SEPARATOR);
+                self.roll_seeded(parent.map(|(parent, _)| parent), nesting, threshold, rng)
+            }
+            _ => picked,
+        }
+    }
+
+    /// Pick a random variant of the named item from the specified branch, given a PRNG
+    ///
+    /// Variants are items of this branch sharing the same name, picked according to their weight.
+    ///
+    /// Returns `Some(Item)` or `None` if no item has this name
+    ///
+    pub fn roll_variant<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        name: &str,
+        rng: &mut R,
+    ) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.branch_at(catalog_path);
+
+        let variants: Vec<&Item> = branch
+            .items
+            .iter()
+            .filter(|item| item.name == name)
+            .collect();
+        let index = pick::weighted_u32(rng, variants.iter().map(|item| item.weight))?;
+
+        Some(variants[index])
+    }
+
+    /// Pick `N` random items from the specified branch, given a PRNG
+    ///
+    /// Returns an array of `Option<Item>`, no `Vec` is allocated for the results
+    ///
+    pub fn roll_batch<const N: usize, R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        nesting: i16,
+        threshold: f32,
+        rng: &mut R,
+    ) -> [Option<&Item<'a>>; N]
+    where
+        R: Rng + ?Sized,
+    {
+        std::array::from_fn(|_| self.roll_seeded(catalog_path, nesting, threshold, rng))
+    }
+
+    /// Pick a random item anywhere in that branch
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_any(&self) -> Option<&Item<'a>> {
+        self.roll_seeded(ROOT, i16::MAX, 1.0, &mut ChaCha20Rng::from_entropy())
+    }
+
+    /// Pick a random item anywhere in that branch, with the PRNG held by this lootbag
+    ///
+    /// See [`Lootr::roll_next()`](crate::Lootr::roll_next)
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_any_next(&mut self) -> Option<Item<'a>> {
+        self.roll_next(ROOT, i16::MAX, 1.0)
+    }
+
+    /// Pick a random item anywhere in the specified branch, skipping items still in cooldown
+    ///
+    /// The picked item cooldown starts at `now`.
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_with_cooldown<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        tracker: &mut CooldownTracker<'a>,
+        now: Instant,
+        rng: &mut R,
+    ) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.branch_at(catalog_path);
+
+        let item = branch.random_pick(i16::MAX, 1.0, self.threshold_dist, rng, &|item| {
+            tracker.is_ready(&item.name, now)
+        })?;
+        tracker.record(item.name.clone(), now);
+
+        Some(item)
+    }
+
+    /// Pick a random item anywhere in the specified branch, with explicit per-depth weights, given a PRNG
+    ///
+    /// `depth_weights[d]` is the weight of items `d` levels below the branch,
+    /// deeper items use the last weight. A depth is picked first, then an item at that depth.
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_depth_weighted<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        depth_weights: &[f32],
+        rng: &mut R,
+    ) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.branch_at(catalog_path);
+
+        let mut levels: Vec<Vec<&Item>> = vec![];
+        branch.walk("", &mut |path, b| {
+            let depth = path.split(SEPARATOR).filter(|s| !s.is_empty()).count();
+            if levels.len() <= depth {
+                levels.resize(depth + 1, vec![]);
+            }
+            levels[depth].extend(b.items.iter());
+        });
+
+        let last = *depth_weights.last()?;
+        let weights = levels
+            .iter()
+            .enumerate()
+            .map(|(depth, items)| match items.is_empty() {
+                true => 0.0,
+                false => depth_weights.get(depth).copied().unwrap_or(last),
+            });
+
+        let depth = pick::weighted(rng, weights)?;
+
+        pick::choose(rng, &levels[depth]).copied()
+    }
+
+    /// Pick a random item of at least the given rarity, anywhere in the specified branch, given a PRNG
+    ///
+    /// If no item reaches `min_rarity`, one of the highest-rarity items available is picked instead.
+    ///
+    /// Returns `Some(Item)` or `None` if the branch is empty
+    ///
+    pub fn roll_best_effort_rarity<R>(
+        &self,
+        catalog_path: Option<&'a str>,
+        min_rarity: Rarity,
+        rng: &mut R,
+    ) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.branch_at(catalog_path);
+
+        let items = branch.all_item_refs();
+        let min_rarity = Some(min_rarity);
+
+        let eligible: Vec<&Item> = items
+            .iter()
+            .filter(|item| item.rarity >= min_rarity)
+            .copied()
+            .collect();
+
+        if !eligible.is_empty() {
+            return pick::choose(rng, &eligible).copied();
+        }
+
+        let best = items.iter().map(|item| item.rarity).max()?;
+        let bests: Vec<&Item> = items
+            .iter()
+            .filter(|item| item.rarity == best)
+            .copied()
+            .collect();
+
+        pick::choose(rng, &bests).copied()
+    }
+
+    /// Pick a random item anywhere in that branch, among items matching the predicate, given a PRNG
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_any_filtered<F, R>(&self, pred: F, rng: &mut R) -> Option<&Item<'a>>
+    where
+        F: Fn(&Item) -> bool,
+        R: Rng + ?Sized,
+    {
+        self.random_pick(i16::MAX, 1.0, self.threshold_dist, rng, &|item| pred(item))
+    }
+
+    /// Pick a random item anywhere in that branch, weighted by its rarity, given a PRNG
+    ///
+    /// Thresholds do not decay with depth. Items without rarity, or with a rarity missing
+    /// from `weights`, have a weight of 1. Rarity weights are multiplied by the item weight.
+    ///
+    /// Returns `Some(Item)` or `None`
+    ///
+    pub fn roll_any_by_rarity<R>(
+        &self,
+        weights: &HashMap<Rarity, f32>,
+        rng: &mut R,
+    ) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let items = self.all_item_refs();
+        let index = pick::weighted(
+            rng,
+            items.iter().map(|item| {
+                let rarity_weight = item.rarity.and_then(|r| weights.get(&r)).copied();
+                rarity_weight.unwrap_or(1.0) * item.weight as f32
+            }),
+        )?;
+
+        Some(items[index])
+    }
+
+    /// Pick one random item per requested tag, anywhere in that branch, given a PRNG
+    ///
+    /// Returns a vec of `Some(Item)`, or `None` when no item carries the tag
+    ///
+    pub fn roll_by_tags<R>(&self, tags: &[&str], rng: &mut R) -> Vec<Option<&Item<'a>>>
+    where
+        R: Rng + ?Sized,
+    {
+        let items = self.all_item_refs();
+
+        tags.iter()
+            .map(|tag| {
+                let tagged: Vec<&Item> = items
+                    .iter()
+                    .filter(|item| item.has_tag(tag))
+                    .copied()
+                    .collect();
+
+                pick::choose(rng, &tagged).copied()
+            })
+            .collect()
+    }
+
+    /// Roll against a looting table
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot(&self, drops: &[Drop]) -> Vec<Item<'a>> {
+        self.loot_seeded(drops, &mut ChaCha20Rng::from_entropy())
+    }
+
+    /// Roll against a looting table, with the PRNG held by this lootbag
+    ///
+    /// See [`Lootr::new_seeded()`](crate::Lootr::new_seeded)
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_next(&mut self, drops: &[Drop]) -> Vec<Item<'a>> {
+        self.with_rng(|bag, rng| bag.loot_seeded(drops, rng))
+    }
+
+    /// Roll against a looting table, given a PRNG
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_seeded<R>(&self, drops: &[Drop], rng: &mut R) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        self.loot_all(drops, false, rng)
+    }
+
+    /// Roll against a looting table, given a PRNG, yielding between `min` and `max` items
+    ///
+    /// The whole table is rolled again until at least `min` items are looted, then extra items are dropped.
+    /// Tables unlikely to yield anything give up after 100 rounds, and may yield less than `min` items.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_count_range<R>(
+        &self,
+        drops: &[Drop],
+        min: usize,
+        max: usize,
+        rng: &mut R,
+    ) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards = self.loot_seeded(drops, rng);
+        let mut rounds = 1;
+
+        while rewards.len() < min && rounds < MAX_LOOT_ROUNDS {
+            rewards.append(&mut self.loot_seeded(drops, rng));
+            rounds += 1;
+        }
+
+        rewards.truncate(max);
+        rewards
+    }
+
+    /// Roll against a looting table, pairing each item with the path of the branch it was rolled from
+    ///
+    /// Returns a vec of path and Item
+    ///
+    pub fn loot_with_paths(&self, drops: &[Drop]) -> Vec<(String, Item<'a>)> {
+        self.loot_with_paths_seeded(drops, &mut ChaCha20Rng::from_entropy())
+    }
+
+    /// Roll against a looting table, given a PRNG, pairing each item with the path of the branch it was rolled from
+    ///
+    /// Paths are full, e.g. `equipment/leather`, and empty for items of the current level.
+    /// Stacked copies of an item share the same path.
+    /// The same PRNG state yields the [`Lootr::loot_seeded()`](crate::Lootr::loot_seeded) items.
+    ///
+    /// Returns a vec of path and Item
+    ///
+    pub fn loot_with_paths_seeded<R>(&self, drops: &[Drop], rng: &mut R) -> Vec<(String, Item<'a>)>
+    where
+        R: Rng + ?Sized,
+    {
+        let (rewards, sources) = self.loot_all_traced(drops, rng);
+
+        sources
+            .into_iter()
+            .map(|(_, path)| path)
+            .zip(rewards)
+            .collect()
+    }
+
+    /// Roll against a looting table, given a PRNG, yielding at most `max_items` items
+    ///
+    /// Drops are rolled in order until `max_items` items are looted, the stack of the last drop is truncated if needed.
+    /// Put the most important drops first.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_capped_seeded<R>(
+        &self,
+        drops: &[Drop],
+        max_items: usize,
+        rng: &mut R,
+    ) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards: Vec<Item> = vec![];
+        let mut taken = HashSet::new();
+
+        for d in drops {
+            if rewards.len() >= max_items {
+                break;
+            }
+
+            rewards.append(&mut self.loot_drop(d, d.luck, &mut taken, rng));
+        }
+
+        rewards.truncate(max_items);
+        rewards
+    }
+
+    /// Roll against a looting table, given a PRNG, preferring branchs which did not yield yet
+    ///
+    /// Items of the branchs which already yielded are skipped, unless no other item is left
+    /// under the drop path, within the drop depth.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_distinct_branches<R>(&self, drops: &[Drop], rng: &mut R) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards: Vec<Item> = vec![];
+        let mut used: Vec<&Lootr> = vec![];
+        let mut taken = HashSet::new();
+
+        for d in drops {
+            let is_fresh = |item: &Item<'a>| {
+                !used
+                    .iter()
+                    .any(|b| b.items.iter().any(|i| std::ptr::eq(i, item)))
+            };
+
+            let branch = self.branch_at(d.path);
+
+            let picked = match branch.item_refs_within(d.depth).into_iter().any(is_fresh) {
+                true => self.pick_loot(d, d.luck, &taken, rng, &is_fresh),
+                false => self.pick_loot(d, d.luck, &taken, rng, &|_| true),
+            };
+
+            if let Some((trail, item)) = picked {
+                used.extend(self.trail_branch(&trail));
+                let mut items = self.stack_drop(d, &trail, item, rng);
+                taken.extend(items.iter().map(|item| item.name.clone()));
+                rewards.append(&mut items);
+            }
+        }
+
+        rewards
+    }
+
+    /// Roll against a looting table, given a PRNG, yielding items with distinct names
+    ///
+    /// Every drop is considered `unique`: it yields a single item, and never an item named like
+    /// an already looted one. Branchs with too few distinct items yield less items.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_unique_seeded<R>(&self, drops: &[Drop], rng: &mut R) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        self.loot_all(drops, true, rng)
+    }
+
+    /// Roll against a looting table, given a PRNG, only yielding item names
+    ///
+    /// Items are not cloned, unless a modifier has to be applied to get its name.
+    /// Names are returned as `Cow`: borrowed names are not copied, while a modifier
+    /// may rename an item, e.g. with `format!`, and its new name has to be owned.
+    /// The same PRNG state yields the names of the [`Lootr::loot_seeded()`](crate::Lootr::loot_seeded) items,
+    /// unique and guaranteed drops included, as long as modifiers do not name items after branch default props.
+    ///
+    /// Returns a vec of item names
+    ///
+    pub fn loot_names<R>(&self, drops: &[Drop], rng: &mut R) -> Vec<Cow<'a, str>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut names = vec![];
+        let mut taken = HashSet::new();
+
+        for d in drops {
+            let (trail, item) = match self.pick_loot(d, d.luck, &taken, rng, &|_| true) {
+                None => continue,
+                Some(picked) => picked,
+            };
+
+            let stack_max = Self::stack_size(d, rng);
+            let modifiers = self.modifier_pool(&trail);
+            let start = names.len();
+
+            names.extend((0..stack_max).map(|_| {
+                if !modifiers.is_empty() && d.modify {
+                    let modifier = pick::choose(rng, &modifiers).unwrap();
+                    modifier(item.clone()).name
+                } else {
+                    item.name.clone()
+                }
+            }));
+            taken.extend(names[start..].iter().cloned());
+        }
+
+        names
+    }
+
+    /// Roll against a looting table, given a PRNG, computing each drop luck with `luck_fn`
+    ///
+    /// The static `luck` of each drop is ignored, `luck_fn` receives the drop and returns the luck to use.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_with_luck_fn<F, R>(&self, drops: &[Drop], luck_fn: F, rng: &mut R) -> Vec<Item<'a>>
+    where
+        F: Fn(&Drop) -> f32,
+        R: Rng + ?Sized,
+    {
+        let mut rewards: Vec<Item> = vec![];
+        let mut taken = HashSet::new();
+
+        for d in drops {
+            rewards.append(&mut self.loot_drop(d, luck_fn(d), &mut taken, rng));
+        }
+
+        rewards
+    }
+
+    /// Roll against a looting table, given a PRNG, with a chance to upgrade each picked item
+    ///
+    /// An upgraded item is swapped with a sibling of the next higher rarity tier in the same branch.
+    /// Items without a higher rarity sibling are kept as is.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_with_upgrade<R>(
+        &self,
+        drops: &[Drop],
+        upgrade_chance: f32,
+        rng: &mut R,
+    ) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards: Vec<Item> = vec![];
+        let mut taken = HashSet::new();
+
+        for d in drops {
+            if let Some((trail, item)) = self.pick_loot(d, d.luck, &taken, rng, &|_| true) {
+                let item = match pick::unit(rng) < upgrade_chance {
+                    true => self.upgrade_of(&trail, item, rng).unwrap_or(item),
+                    false => item,
+                };
+
+                let mut items = self.stack_drop(d, &trail, item, rng);
+                taken.extend(items.iter().map(|item| item.name.clone()));
+                rewards.append(&mut items);
+            }
+        }
+
+        rewards
+    }
+
+    /// Roll against a looting table, with a PRNG seeded from `seed`
+    ///
+    /// Returns a vec of Item, and the [`LootMeta`](crate::LootMeta) needed to replay this loot
+    ///
+    pub fn loot_seeded_with_meta(&self, drops: &[Drop], seed: u64) -> (Vec<Item<'a>>, LootMeta) {
+        let rng = &mut ChaCha20Rng::seed_from_u64(seed);
+        let mut rewards: Vec<Item> = vec![];
+        let mut drop_indices = vec![];
+        let mut taken = HashSet::new();
+
+        for (i, d) in drops.iter().enumerate() {
+            let mut items = self.loot_drop(d, d.luck, &mut taken, rng);
+
+            drop_indices.extend(items.iter().map(|_| i));
+            rewards.append(&mut items);
+        }
+
+        (rewards, LootMeta { seed, drop_indices })
+    }
+
+    /// Roll against a looting table, with a PRNG seeded from `seed`, logging each looted item
+    ///
+    /// The same seed and drops yield the same items as [`Lootr::loot_seeded()`](crate::Lootr::loot_seeded)
+    /// with a ChaCha20 PRNG.
+    ///
+    /// Returns a vec of Item, and the [`LootLog`](crate::LootLog) of this loot
+    ///
+    pub fn loot_logged<'d>(&self, drops: &[Drop<'d>], seed: u64) -> (Vec<Item<'a>>, LootLog<'d>)
+    where
+        'a: 'd,
+    {
+        let rng = &mut ChaCha20Rng::seed_from_u64(seed);
+        let (rewards, sources) = self.loot_all_traced(drops, rng);
+
+        let entries = sources
+            .into_iter()
+            .zip(&rewards)
+            .map(|((drop_index, path), item)| LootLogEntry {
+                drop_index,
+                path,
+                name: item.name.clone(),
+            })
+            .collect();
+
+        let log = LootLog {
+            seed,
+            drops: drops.to_vec(),
+            entries,
+        };
+
+        (rewards, log)
+    }
+
+    /// Roll against a looting table, given a PRNG, and shuffle the rewards
+    ///
+    /// The rewards order does not reveal the drops order or stacks.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_shuffled<R>(&self, drops: &[Drop], rng: &mut R) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards = self.loot_seeded(drops, rng);
+        pick::shuffle(rng, &mut rewards);
+
+        rewards
+    }
+
+    /// Roll against a list of sets, given a PRNG
+    ///
+    /// A set yields all its items, or nothing if the roll fails or any item is missing.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_sets<R>(&self, sets: &[DropSet], rng: &mut R) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards: Vec<Item> = vec![];
+
+        for set in sets {
+            let branch = self.branch_at(set.path);
+
+            let items = branch.all_item_refs();
+            let found: Option<Vec<&Item>> = set
+                .items
+                .iter()
+                .map(|name| items.iter().find(|item| item.name == *name).copied())
+                .collect();
+
+            if let Some(found) = found {
+                if pick::unit(rng) < set.luck {
+                    rewards.extend(found.into_iter().cloned());
+                }
+            }
+        }
+
+        rewards
+    }
+
+    /// Roll against a looting table, given the player inventory and a PRNG
+    ///
+    /// Drops whose [`requires`](crate::drops::Drop::requires) keys are not all
+    /// present in the inventory are skipped.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_with_inventory<R>(
+        &self,
+        drops: &[Drop],
+        inventory: &HashSet<&str>,
+        rng: &mut R,
+    ) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards: Vec<Item> = vec![];
+        let mut taken = HashSet::new();
+
+        for d in drops {
+            if !d.requires.iter().all(|key| inventory.contains(key)) {
+                continue;
+            }
+
+            rewards.append(&mut self.loot_drop(d, d.luck, &mut taken, rng));
+        }
+
+        rewards
+    }
+
+    /// Roll `total_picks` times against a looting table, given a PRNG
+    ///
+    /// Each drop luck is used as a relative weight to select which drop is rolled,
+    /// the selected drop is then rolled with a luck of 1.0.
+    ///
+    /// Returns a vec of Item
+    ///
+    pub fn loot_normalized<R>(
+        &self,
+        drops: &[Drop],
+        total_picks: usize,
+        rng: &mut R,
+    ) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards: Vec<Item> = vec![];
+        let mut taken = HashSet::new();
+
+        for _ in 0..total_picks {
+            let d = match pick::weighted(rng, drops.iter().map(|d| d.luck)) {
+                None => break,
+                Some(index) => &drops[index],
+            };
+            rewards.append(&mut self.loot_drop(d, 1.0, &mut taken, rng));
+        }
+
+        rewards
+    }
+
+    /// Sample a looting table, given a PRNG
+    ///
+    /// Returns a [`DropReport`](crate::report::DropReport) holding the observed statistics
+    ///
+    pub fn drop_report<R>(&self, drops: &[Drop], samples: usize, rng: &mut R) -> DropReport
+    where
+        R: Rng + ?Sized,
+    {
+        let mut total = 0;
+        let mut hits = vec![0; drops.len()];
+        let mut stacked = vec![0; drops.len()];
+        let mut seen = BTreeMap::<String, usize>::new();
+
+        for _ in 0..samples {
+            let mut taken = HashSet::new();
+
+            for (i, d) in drops.iter().enumerate() {
+                let rewards = self.loot_drop(d, d.luck, &mut taken, rng);
+
+                if !rewards.is_empty() {
+                    hits[i] += 1;
+                    stacked[i] += rewards.len();
+                }
+
+                total += rewards.len();
+            }
+
+            for name in taken {
+                *seen.entry(name.to_string()).or_default() += 1;
+            }
+        }
+
+        let ratio = |count: usize, over: usize| match over {
+            0 => 0.0,
+            _ => count as f64 / over as f64,
+        };
+
+        DropReport {
+            samples,
+            expected_items: ratio(total, samples),
+            item_probabilities: seen
+                .into_iter()
+                .map(|(name, count)| (name, ratio(count, samples)))
+                .collect(),
+            expected_stacks: hits
+                .iter()
+                .zip(stacked.iter())
+                .map(|(&hit, &stack)| ratio(stack, hit))
+                .collect(),
+        }
+    }
+
+    /// Run `f` with the held PRNG, seeding one from entropy if there is none
+    fn with_rng<T, F>(&mut self, f: F) -> T
+    where
+        F: FnOnce(&Self, &mut ChaCha20Rng) -> T,
+    {
+        let mut rng = self.rng.take().unwrap_or_else(ChaCha20Rng::from_entropy);
+        let output = f(self, &mut rng);
+        self.rng = Some(rng);
+
+        output
+    }
+
+    fn loot_all<R>(&self, drops: &[Drop], all_unique: bool, rng: &mut R) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards: Vec<Item> = vec![];
+        let mut taken = HashSet::new();
+
+        for d in drops {
+            let mut items = match all_unique && !d.unique {
+                true => {
+                    let unique = Drop {
+                        unique: true,
+                        ..d.clone()
+                    };
+                    self.loot_drop(&unique, d.luck, &mut taken, rng)
+                }
+                false => self.loot_drop(d, d.luck, &mut taken, rng),
+            };
+            rewards.append(&mut items);
+        }
 
-use crate::{
-    drops::Drop,
-    item::{Item, Modifier},
-};
+        rewards
+    }
 
-pub const ROOT: Option<&str> = None;
-const SEPARATOR: char = '/';
+    /// Same as `loot_all` without `all_unique`, also returning the drop index and branch path of each item
+    fn loot_all_traced<R>(
+        &self,
+        drops: &[Drop],
+        rng: &mut R,
+    ) -> (Vec<Item<'a>>, Vec<(usize, String)>)
+    where
+        R: Rng + ?Sized,
+    {
+        let mut rewards: Vec<Item> = vec![];
+        let mut sources = vec![];
+        let mut taken = HashSet::new();
 
-pub struct Lootr<'a> {
-    items: Vec<Item<'a>>,
-    branchs: BTreeMap<&'a str, Lootr<'a>>,
-    modifiers: Vec<Modifier>,
-}
+        for (i, d) in drops.iter().enumerate() {
+            let (trail, mut items) = self.loot_drop_traced(d, d.luck, &mut taken, rng);
+            let path = trail.join(&SEPARATOR.to_string());
 
-impl<'a> fmt::Display for Lootr<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write_tree(f, &self.fmt_node("ROOT"))
+            sources.extend(items.iter().map(|_| (i, path.clone())));
+            rewards.append(&mut items);
+        }
+
+        (rewards, sources)
     }
-}
 
-impl<'a> Lootr<'a> {
-    /// Create a new lootbag
-    ///
-    pub fn new() -> Self {
-        Self::from(vec![])
+    fn loot_drop<R>(
+        &self,
+        d: &Drop,
+        luck: f32,
+        taken: &mut HashSet<Cow<'a, str>>,
+        rng: &mut R,
+    ) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        self.loot_drop_traced(d, luck, taken, rng).1
     }
 
-    /// Create a new lootbag from given items
+    /// Loot a drop, along with the trail of branch names leading to the looted item
     ///
-    pub fn from(items: Vec<Item<'a>>) -> Self {
-        Self {
-            items,
-            branchs: BTreeMap::new(),
-            modifiers: vec![],
+    /// The names of the looted items are added to `taken`.
+    fn loot_drop_traced<R>(
+        &self,
+        d: &Drop,
+        luck: f32,
+        taken: &mut HashSet<Cow<'a, str>>,
+        rng: &mut R,
+    ) -> (Vec<&str>, Vec<Item<'a>>)
+    where
+        R: Rng + ?Sized,
+    {
+        match self.pick_loot(d, luck, taken, rng, &|_| true) {
+            None => (vec![], vec![]),
+            Some((trail, item)) => {
+                let items = self.stack_drop(d, &trail, item, rng);
+                taken.extend(items.iter().map(|item| item.name.clone()));
+                (trail, items)
+            }
         }
     }
 
-    /// Return this lootbag branchs
+    /// Pick the item of a drop, as every loot does
     ///
-    pub fn branchs(&self) -> &BTreeMap<&str, Lootr<'a>> {
-        &self.branchs
-    }
+    /// Unique drops skip the names in `taken`, and guaranteed drops yielding nothing are rolled again,
+    /// without the luck gate.
+    fn pick_loot<'s, R, F>(
+        &'s self,
+        d: &Drop,
+        luck: f32,
+        taken: &HashSet<Cow<'a, str>>,
+        rng: &mut R,
+        filter: &F,
+    ) -> Option<(Vec<&'s str>, &'s Item<'a>)>
+    where
+        R: Rng + ?Sized,
+        F: Fn(&Item<'a>) -> bool,
+    {
+        let filter = |item: &Item<'a>| filter(item) && !(d.unique && taken.contains(&item.name));
 
-    /// Return this lootbag items (at this level)
-    ///
-    pub fn items(&self) -> &Vec<Item> {
-        &self.items
+        let mut picked = self.pick_drop(d, luck, rng, &filter);
+        let mut attempts = 1;
+
+        while picked.is_none() && d.guaranteed && attempts < MAX_LOOT_ROUNDS {
+            picked = self.pick_drop(d, 1.0, rng, &filter);
+            attempts += 1;
+        }
+
+        picked
     }
 
-    /// Return this lootbag items count (at this level)
-    ///
-    pub fn self_count(&self) -> usize {
-        self.items.len()
+    /// Pick the item of a drop, and the full trail of branch names leading to it
+    fn pick_drop<'s, R, F>(
+        &'s self,
+        d: &Drop,
+        luck: f32,
+        rng: &mut R,
+        filter: &F,
+    ) -> Option<(Vec<&'s str>, &'s Item<'a>)>
+    where
+        R: Rng + ?Sized,
+        F: Fn(&Item<'a>) -> bool,
+    {
+        if luck <= 0.0 {
+            return None;
+        }
+
+        let branch = self.branch_at(d.path);
+
+        let filter =
+            &|item: &Item<'a>| filter(item) && d.require_tag.is_none_or(|tag| item.has_tag(tag));
+
+        let picked = match d.item {
+            Some(name) => branch
+                .find_traced(name)
+                .filter(|(_, item)| filter(item))
+                .filter(|_| pick::unit(rng) < luck),
+            None => {
+                let tier = |item: &Item<'a>| filter(item) && item.rarity == d.rarity;
+                let has_tier = d.rarity.is_some()
+                    && branch
+                        .iter_detailed()
+                        .any(|(depth, _, item)| depth <= d.depth && tier(item));
+
+                // without any item of the target rarity in reach, fall back to all items
+                match has_tier {
+                    true => branch.random_pick_traced(
+                        d.depth,
+                        luck,
+                        self.threshold_dist,
+                        rng,
+                        &tier,
+                        &mut RollProfile::default(),
+                    ),
+                    false => branch.random_pick_traced(
+                        d.depth,
+                        luck,
+                        self.threshold_dist,
+                        rng,
+                        filter,
+                        &mut RollProfile::default(),
+                    ),
+                }
+            }
+        };
+
+        picked
+            .filter(|(_, item)| d.filter.is_none_or(|f| f(item)))
+            .map(|(trail, item)| (self.full_trail(d.path, trail), item))
     }
 
-    /// Return this lootbag items count (including any sublevel)
-    ///
-    pub fn all_count(&self) -> usize {
-        self.all_items().len()
+    /// Stack copies of a picked item, applying modifiers of the branches along the trail
+    fn stack_drop<R>(&self, d: &Drop, trail: &[&str], item: &Item<'a>, rng: &mut R) -> Vec<Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let stack_max = Self::stack_size(d, rng);
+        let modifiers = self.modifier_pool(trail);
+        let item = &self.inherit_props(trail, item);
+
+        (0..stack_max)
+            .map(|_| {
+                if !modifiers.is_empty() && d.modify {
+                    let modifier = pick::choose(rng, &modifiers).unwrap();
+                    modifier(item.clone())
+                } else {
+                    item.clone()
+                }
+            })
+            .collect()
     }
 
-    /// Add an item at this level
-    ///
-    /// Returns the current lootbag
+    /// The branch at the given path, or this lootbag for the root
     ///
-    pub fn add(&mut self, item: Item<'a>) -> &mut Self {
-        self.items.push(item);
+    /// Panics naming the missing segment if the branch does not exist.
+    fn branch_at(&self, path: Option<&str>) -> &Lootr<'a> {
+        match path {
+            None => self,
+            Some(path) => self
+                .try_branch(path)
+                .unwrap_or_else(|err| panic!("cannot use path {path:?}, {err}")),
+        }
+    }
 
-        self
+    /// The branch at the end of a trail of branch names
+    fn trail_branch(&self, trail: &[&str]) -> Option<&Lootr<'a>> {
+        trail
+            .iter()
+            .try_fold(self, |acc, name| acc.branchs.get(*name))
     }
 
-    /// Add an item in the given branch
-    ///
-    /// Returns the current lootbag
-    ///
-    pub fn add_in(&mut self, item: Item<'a>, path: &'a str) -> &mut Self {
-        match self.branch_mut(path) {
-            None => panic!("this path does not exist"),
-            Some(branch) => branch.add(item),
-        };
+    /// Pick a sibling of the next higher rarity tier found in the branch at the end of the trail
+    fn upgrade_of<'s, R>(
+        &'s self,
+        trail: &[&str],
+        item: &Item<'a>,
+        rng: &mut R,
+    ) -> Option<&'s Item<'a>>
+    where
+        R: Rng + ?Sized,
+    {
+        let branch = self.trail_branch(trail)?;
 
-        self
-    }
+        let next = branch
+            .items
+            .iter()
+            .map(|i| i.rarity)
+            .filter(|rarity| *rarity > item.rarity)
+            .min()?;
 
-    /// Returns the branch at the given path.
-    ///
-    pub fn branch_mut(&mut self, path: &'a str) -> Option<&mut Lootr<'a>> {
-        let cname = path.trim_matches(SEPARATOR);
+        let siblings: Vec<&Item> = branch.items.iter().filter(|i| i.rarity == next).collect();
 
-        // simple case
-        if self.branchs.contains_key(&cname) {
-            return self.branchs.get_mut(&cname);
-        }
+        pick::choose(rng, &siblings).copied()
+    }
 
-        if !cname.contains(SEPARATOR) {
-            return None;
+    fn walk<'s, F>(&'s self, path: &str, f: &mut F)
+    where
+        F: FnMut(&str, &'s Lootr<'a>),
+    {
+        f(path, self);
+
+        for (name, branch) in &self.branchs {
+            let subpath = match path {
+                "" => name.to_string(),
+                _ => format!("{path}{SEPARATOR}{name}"),
+            };
+            branch.walk(&subpath, f);
         }
+    }
 
-        // segmented path
-        let leaf = path
-            .trim_matches(SEPARATOR)
-            .split(SEPARATOR)
-            .fold(self, |acc, s| acc.branch_mut(s).unwrap());
+    fn all_item_refs(&self) -> Vec<&Item<'a>> {
+        let mut bag: Vec<&Item> = self.items.iter().collect();
+
+        for b in self.branchs.values() {
+            bag.append(&mut b.all_item_refs());
+        }
 
-        Some(leaf)
+        bag
     }
 
-    /// Returns the branch at the given path.
-    /// If the branch does not exit yet, `None` is returned
-    ///
-    pub fn branch(&self, path: &'a str) -> Option<&Lootr<'a>> {
-        let cname = path.trim_matches(SEPARATOR);
+    /// Items of this level, and of the sub branchs down to `depth` levels, like rolls reach them
+    fn item_refs_within(&self, depth: i16) -> Vec<&Item<'a>> {
+        let mut bag: Vec<&Item> = self.items.iter().collect();
 
-        // simple case
-        if self.branchs.contains_key(&cname) {
-            return self.branchs.get(&cname);
+        if depth > 0 {
+            for b in self.branchs.values() {
+                bag.append(&mut b.item_refs_within(depth - 1));
+            }
         }
 
-        if !cname.contains(SEPARATOR) {
-            return None;
+        bag
+    }
+
+    fn stack_size<R>(d: &Drop, rng: &mut R) -> u32
+    where
+        R: Rng + ?Sized,
+    {
+        // unique drops yield a single item
+        if d.unique {
+            return 1;
         }
 
-        // segmented path
-        let leaf = path
-            .trim_matches(SEPARATOR)
-            .split(SEPARATOR)
-            .fold(self, |acc, s| match acc.branch(s) {
-                Some(branch) => branch,
-                _ => panic!("this branch does not exist: {s}"),
-            });
+        let weighted = d.stack_dist.as_ref().and_then(|dist| {
+            pick::weighted(rng, dist.iter().map(|(_, weight)| *weight)).map(|index| dist[index].0)
+        });
 
-        Some(leaf)
+        // an invalid distribution falls back to the stack range
+        match weighted {
+            Some(size) => size,
+            None => pick::range_u32(rng, d.stack.clone()),
+        }
     }
 
-    /// Add a branch, return self (the owner)
-    ///
-    pub fn add_branch(&mut self, path: &'a str, branch: Lootr<'a>) -> &mut Self {
-        self.branchs.insert(path, branch);
-        self
+    /// Find the first item with the given name, and the branch names leading to it
+    fn find_traced<'s>(&'s self, name: &str) -> Option<(Vec<&'s str>, &'s Item<'a>)> {
+        if let Some(item) = self.items.iter().find(|item| item.name == name) {
+            return Some((vec![], item));
+        }
+
+        self.branchs.iter().find_map(|(branch_name, b)| {
+            b.find_traced(name).map(|(mut trail, item)| {
+                trail.insert(0, &**branch_name);
+                (trail, item)
+            })
+        })
     }
 
-    /// Return all items in the current and nested branchs
-    ///
-    pub fn all_items(&self) -> Vec<Item> {
-        let mut bag = vec![];
+    /// The branch names along `path`, borrowed from this lootbag, followed by `trail`
+    fn full_trail<'s>(&'s self, path: Option<&str>, trail: Vec<&'s str>) -> Vec<&'s str> {
+        let segments = path
+            .into_iter()
+            .flat_map(|path| path.trim_matches(SEPARATOR).split(SEPARATOR));
+
+        let mut full = vec![];
+        let mut branch = self;
+
+        for segment in segments {
+            match branch.branchs.get_key_value(segment) {
+                None => break,
+                Some((name, b)) => {
+                    full.push(&**name);
+                    branch = b;
+                }
+            }
+        }
+        full.extend(trail);
 
-        bag.append(&mut self.items.clone());
+        full
+    }
 
-        for b in self.branchs.values() {
-            bag.append(&mut b.all_items().to_vec());
+    /// Modifiers of this level and of every branch along the trail
+    fn modifier_pool<'s>(&'s self, trail: &[&str]) -> Vec<&'s Modifier<'a>> {
+        let mut pool: Vec<&(&str, Modifier)> = self.modifiers.iter().collect();
+        let mut branch = self;
+
+        for name in trail {
+            match branch.branchs.get(*name) {
+                None => break,
+                Some(b) => {
+                    pool.extend(b.modifiers.iter());
+                    branch = b;
+                }
+            }
         }
 
-        bag
+        if self.sorted_modifiers {
+            pool.sort_by_key(|(name, _)| *name);
+        }
+
+        pool.into_iter().map(|(_, modifier)| modifier).collect()
     }
 
-    /// Add a modifier
-    ///
-    pub fn add_modifier(&mut self, modifier: Modifier) -> &mut Self {
-        self.modifiers.push(modifier);
-        self
+    /// Clone the item, with the default props of the branchs along the trail
+    fn inherit_props(&self, trail: &[&str], item: &Item<'a>) -> Item<'a> {
+        let mut props = self.default_props.clone();
+        let mut branch = self;
+
+        for name in trail {
+            match branch.branchs.get(*name) {
+                None => break,
+                Some(b) => {
+                    props.extend(b.default_props.clone());
+                    branch = b;
+                }
+            }
+        }
+
+        if props.is_empty() {
+            return item.clone();
+        }
+
+        props.extend(item.props.clone().unwrap_or_default());
+
+        Item {
+            props: Some(props),
+            ..item.clone()
+        }
     }
 
-    /// Pick a random item from the specified branch
-    ///
-    /// Returns `Some(Item)` or `None`
-    ///
-    pub fn roll(
+    fn random_pick<R, F>(
         &self,
-        catalog_path: Option<&'a str>,
         nesting: i16,
         threshold: f32,
-    ) -> Option<&Item> {
-        self.roll_seeded(
-            catalog_path,
+        dist: ThresholdDist,
+        rng: &mut R,
+        filter: &F,
+    ) -> Option<&Item<'a>>
+    where
+        R: Rng + ?Sized,
+        F: Fn(&Item<'a>) -> bool,
+    {
+        // nothing can pass a zero threshold, skip the traversal
+        if threshold <= 0.0 {
+            return None;
+        }
+
+        self.random_pick_traced(
             nesting,
             threshold,
-            &mut ChaCha20Rng::from_entropy(),
+            dist,
+            rng,
+            filter,
+            &mut RollProfile::default(),
         )
+        .map(|(_, item)| item)
     }
 
-    /// Pick a random item from the specified branch, given a PRNG
-    ///
-    /// Returns `Some(Item)` or `None`
-    ///
-    pub fn roll_seeded<R>(
-        &self,
-        catalog_path: Option<&'a str>,
+    /// Same as `random_pick`, also returning the branch names leading to the picked item
+    fn random_pick_traced<'s, R, F>(
+        &'s self,
         nesting: i16,
         threshold: f32,
+        dist: ThresholdDist,
         rng: &mut R,
-    ) -> Option<&Item<'a>>
+        filter: &F,
+        profile: &mut RollProfile,
+    ) -> Option<(Vec<&'s str>, &'s Item<'a>)>
     where
         R: Rng + ?Sized,
+        F: Fn(&Item<'a>) -> bool,
     {
-        let branch = match catalog_path {
-            None => self,
-            Some(path) => self.branch(path).unwrap(),
-        };
+        let bag = self.candidate_bag(nesting, threshold, dist, rng, filter, profile);
 
-        branch.random_pick(nesting, threshold, rng)
+        pick::choose(rng, &bag).cloned()
     }
 
-    /// Pick a random item anywhere in that branch
-    ///
-    /// Returns `Some(Item)` or `None`
-    ///
-    pub fn roll_any(&self) -> Option<&Item> {
-        self.roll_seeded(ROOT, i16::MAX, 1.0, &mut ChaCha20Rng::from_entropy())
-    }
+    /// The candidates `random_pick_traced` chooses from: one of this level items, and one per sub branch
+    fn candidate_bag<'s, R, F>(
+        &'s self,
+        nesting: i16,
+        threshold: f32,
+        dist: ThresholdDist,
+        rng: &mut R,
+        filter: &F,
+        profile: &mut RollProfile,
+    ) -> Vec<(Vec<&'s str>, &'s Item<'a>)>
+    where
+        R: Rng + ?Sized,
+        F: Fn(&Item<'a>) -> bool,
+    {
+        profile.branches_visited += 1;
 
-    /// Roll against a looting table
-    ///
-    /// Returns a vec of Item
-    ///
-    pub fn loot(&self, drops: &[Drop]) -> Vec<Item> {
-        self.loot_seeded(drops, &mut ChaCha20Rng::from_entropy())
+        let mut bag: Vec<_> = self
+            .own_candidate(threshold, dist, rng, filter, profile)
+            .into_iter()
+            .collect();
+        bag.append(&mut self.branch_candidates(nesting, threshold, dist, rng, filter, profile));
+
+        bag
     }
 
-    /// Roll against a looting table, given a PRNG
-    ///
-    /// Returns a vec of Item
-    ///
-    pub fn loot_seeded<R>(&self, drops: &[Drop], rng: &mut R) -> Vec<Item>
+    /// Pick an item at this level, if a draw from `dist` passes the threshold
+    fn own_candidate<'s, R, F>(
+        &'s self,
+        threshold: f32,
+        dist: ThresholdDist,
+        rng: &mut R,
+        filter: &F,
+        profile: &mut RollProfile,
+    ) -> Option<(Vec<&'s str>, &'s Item<'a>)>
     where
         R: Rng + ?Sized,
+        F: Fn(&Item<'a>) -> bool,
     {
-        let mut rewards: Vec<Item> = vec![];
+        let eligible: Vec<&Item> = self.items.iter().filter(|item| filter(item)).collect();
 
-        for d in drops {
-            let item = self.roll_seeded(d.path, d.depth, d.luck, rng);
+        profile.items_considered += eligible.len();
 
-            if item.is_none() {
-                continue;
-            }
+        let index = pick::weighted_u32(rng, eligible.iter().map(|item| item.weight))?;
+        let item = eligible[index];
 
-            let citem: Item = item.unwrap().clone();
-            let stack_max = rng.gen_range(d.stack.clone());
-
-            rewards.append(
-                &mut (0..stack_max)
-                    .map(|_| {
-                        if !self.modifiers.is_empty() && d.modify {
-                            let modifier = self.modifiers.choose(rng).unwrap();
-                            modifier(citem.clone())
-                        } else {
-                            citem.clone()
-                        }
-                    })
-                    .collect::<Vec<Item>>(),
-            );
+        if dist.sample(rng) < threshold {
+            Some((vec![], item))
+        } else {
+            profile.rejections += 1;
+            None
         }
-
-        rewards
     }
 
-    fn random_pick<R>(&self, nesting: i16, threshold: f32, rng: &mut R) -> Option<&Item<'a>>
+    /// Pick an item in each sub branch, with a decreasing threshold
+    fn branch_candidates<'s, R, F>(
+        &'s self,
+        nesting: i16,
+        threshold: f32,
+        dist: ThresholdDist,
+        rng: &mut R,
+        filter: &F,
+        profile: &mut RollProfile,
+    ) -> Vec<(Vec<&'s str>, &'s Item<'a>)>
     where
         R: Rng + ?Sized,
+        F: Fn(&Item<'a>) -> bool,
     {
         let mut bag = vec![];
 
-        if let Some(item) = self.items.choose(rng) {
-            if rng.gen::<f32>() < threshold {
-                bag.push(item);
-            }
-        }
-
-        for b in self.branchs.values() {
-            let decrease: f32 = rng.gen_range(0.0001..1.0);
+        for (name, b) in &self.branchs {
+            let decrease = pick::range_f32(rng, 0.0001..1.0);
             let new_threshold = (threshold * decrease).clamp(0.0, 1.0);
             let new_threshold = (new_threshold * 100.0).round() / 100.0;
 
             if nesting > 0 {
-                if let Some(item) = b.random_pick(nesting - 1, new_threshold, rng) {
-                    bag.push(item);
+                if let Some((mut trail, item)) =
+                    b.random_pick_traced(nesting - 1, new_threshold, dist, rng, filter, profile)
+                {
+                    trail.insert(0, &**name);
+                    bag.push((trail, item));
                 }
             }
         }
 
-        bag.choose(rng).copied()
+        bag
     }
 
-    fn fmt_node(&self, name: &str) -> ascii_tree::Tree {
+    fn fmt_node(&self, name: &str, opts: &RenderOptions, depth: usize) -> ascii_tree::Tree {
         let mut children: Vec<ascii_tree::Tree> = vec![];
 
         children.push(Leaf(
             self.items()
                 .iter()
-                .map(|item| format!("{}", item))
+                .map(|item| match opts.show_props {
+                    true => format!("{}", item),
+                    false => item.name.to_string(),
+                })
                 .collect(),
         ));
 
-        let mut branchs: Vec<ascii_tree::Tree> = self
-            .branchs()
-            .iter()
-            .map(|(&name, branch)| branch.fmt_node(name))
-            .collect();
-        children.append(&mut branchs);
+        if opts.max_depth.is_none_or(|max| depth < max) {
+            let mut branchs: Vec<ascii_tree::Tree> = self
+                .branchs()
+                .iter()
+                .map(|(name, branch)| branch.fmt_node(name, opts, depth + 1))
+                .collect();
+            children.append(&mut branchs);
+        }
+
+        let label = match opts.show_counts {
+            true => format!("{} ({})", name, self.all_count()),
+            false => String::from(name),
+        };
+
+        Node(label, children)
+    }
+
+    /// Write this branch, its items and sub branchs as DOT statements, returning the branch node id
+    fn dot_node(&self, name: &str, next_id: &mut usize, output: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        output.push_str(&format!(
+            "    n{id} [label=\"{}\", shape=box];\n",
+            dot_escape(name)
+        ));
+
+        for item in &self.items {
+            let mut props: Vec<String> = item
+                .props_iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect();
+            props.sort();
+
+            let label = std::iter::once(item.name.to_string())
+                .chain(props)
+                .map(|line| dot_escape(&line))
+                .collect::<Vec<_>>()
+                .join("\\n");
+
+            output.push_str(&format!("    n{} [label=\"{label}\"];\n", *next_id));
+            output.push_str(&format!("    n{id} -> n{};\n", *next_id));
+            *next_id += 1;
+        }
+
+        for (name, branch) in &self.branchs {
+            let child = branch.dot_node(name, next_id, output);
+            output.push_str(&format!("    n{id} -> n{child};\n"));
+        }
 
-        Node(String::from(name), children)
+        id
     }
 }
 
+/// Escape a string to be used in a quoted DOT label
+fn dot_escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 #[macro_export]
 macro_rules! a {
     ( $x:expr ) => {
@@ -328,8 +2579,9 @@ macro_rules! bag {
     // ($(@ $b1:ident $($i1:ident $($a1:ident = $v1:expr) *;),* $(@$tail:meta |),* |)*) => {
     // ($(@ $branch:ident $($item:ident $($a1:ident = $v1:expr) *,);* |)*) => { // OK
     // ($(@ $branch:ident $($item:ident $($a1:ident = $v1:expr) *,);* $(@ $b2:ident $($i2:ident $($a2:ident = $v2:expr) *,);* |)* |)*) => { // OK
-    ($
-        (@ $b1:ident $($i1:ident $($a1:ident = $v1:expr) *,)*
+    (
+        $($i0:ident $($a0:ident = $v0:expr) *,)*
+        $(@ $b1:ident $($i1:ident $($a1:ident = $v1:expr) *,)*
             $(@ $b2:ident $($i2:ident $($a2:ident = $v2:expr) *,)*
                 $(@ $b3:ident $($i3:ident $($a3:ident = $v3:expr) *,)*
                 .)*
@@ -337,39 +2589,51 @@ macro_rules! bag {
         .)*
     ) => {
         {
-            let mut loot = Lootr::new();
-            loot.add(Item::named("test"));
+            let mut loot = $crate::Lootr::new();
+
+            $( // for each $i0
+                #[allow(unused_mut)]
+                let mut i0 = $crate::item::Item::named(stringify!($i0));
+                $( // for each $a0
+                    i0.set_prop(stringify!($a0), $crate::bag_value!($v0));
+                )*
+                loot.add(i0);
+            )*
 
             $( // for each $b1
-                let mut b1 = Lootr::new();
+                let mut b1 = $crate::Lootr::new();
 
                 $( // for each $i1
-                    let mut i1 = Item::named(stringify!($i1));
+                    #[allow(unused_mut)]
+                    let mut i1 = $crate::item::Item::named(stringify!($i1));
                     $( // for each $a1
-                        i1.set_prop(stringify!($a1), stringify!($v1));
+                        i1.set_prop(stringify!($a1), $crate::bag_value!($v1));
                     )*
                     b1.add(i1);
                 )*
 
                 $( // for each $b2
-                    let mut b2 = Lootr::new();
+                    let mut b2 = $crate::Lootr::new();
 
                     $( // for each $i1
-                        let mut i2 = Item::named(stringify!($i2));
+                        #[allow(unused_mut)]
+                        let mut i2 = $crate::item::Item::named(stringify!($i2));
                         $( // for each $a1
-                            i2.set_prop(stringify!($a2), stringify!($v2));
+                            i2.set_prop(stringify!($a2), $crate::bag_value!($v2));
                         )*
                         b2.add(i2);
                     )*
 
                     $( // for each $b3
-                        let mut b3 = Lootr::new();
+                        let mut b3 = $crate::Lootr::new();
 
                         $( // for each $i3
 
-                            let mut i3 = Item::named(stringify!($i3));
+                            #[allow(unused_mut)]
+
+                            let mut i3 = $crate::item::Item::named(stringify!($i3));
                             $( // for each $a3
-                                i3.set_prop(stringify!($a3), stringify!($v3));
+                                i3.set_prop(stringify!($a3), $crate::bag_value!($v3));
                             )*
                             b3.add(i3);
                         )*
@@ -389,3 +2653,15 @@ macro_rules! bag {
         println("recursiooooooonnnn !!");
     };
 }
+
+/// Turn a `bag!` prop value into a string: literals are stored as is, other tokens are stringified
+#[doc(hidden)]
+#[macro_export]
+macro_rules! bag_value {
+    ($v:literal) => {
+        concat!($v)
+    };
+    ($v:expr) => {
+        stringify!($v)
+    };
+}
@@ -0,0 +1,57 @@
+//! Module containing built-in modifiers used in Lootr.
+//!
+//! Each helper returns a [`Modifier`](crate::item::Modifier), to be given to
+//! [`Lootr::add_modifier()`](crate::Lootr::add_modifier).
+//!
+//! Renamed items own their new name, the looted item borrowing nothing from the modifier.
+//!
+
+use std::borrow::Cow;
+
+use crate::item::{Item, Modifier};
+
+/// Create a modifier prepending `prefix` to the item name.
+///
+/// # Examples
+///
+/// ```
+/// use lootr::{drops::DropBuilder, item::Item, modifiers, Lootr};
+///
+/// let mut loot = Lootr::from(vec![Item::a("Sword")]);
+/// loot.add_modifier(modifiers::prefix("Flaming "));
+///
+/// let rewards = loot.loot(&[DropBuilder::new().luck(1.0).modified().build()]);
+///
+/// assert_eq!(rewards[0].name, "Flaming Sword");
+/// ```
+pub fn prefix<'a>(prefix: &'a str) -> Modifier<'a> {
+    Box::new(move |item: Item<'a>| rename(item, |name| format!("{prefix}{name}")))
+}
+
+/// Create a modifier appending `suffix` to the item name.
+///
+/// # Examples
+///
+/// ```
+/// use lootr::{drops::DropBuilder, item::Item, modifiers, Lootr};
+///
+/// let mut loot = Lootr::from(vec![Item::a("Axe")]);
+/// loot.add_modifier(modifiers::suffix(" of Doom"));
+///
+/// let rewards = loot.loot(&[DropBuilder::new().luck(1.0).modified().build()]);
+///
+/// assert_eq!(rewards[0].name, "Axe of Doom");
+/// ```
+pub fn suffix<'a>(suffix: &'a str) -> Modifier<'a> {
+    Box::new(move |item: Item<'a>| rename(item, |name| format!("{name}{suffix}")))
+}
+
+fn rename<'a, F>(item: Item<'a>, f: F) -> Item<'a>
+where
+    F: FnOnce(&str) -> String,
+{
+    Item {
+        name: Cow::Owned(f(&item.name)),
+        ..item
+    }
+}
@@ -0,0 +1,209 @@
+//! Module containing the random selection routines used in Lootr.
+//!
+//! Every random decision Lootr makes goes through these functions, which only
+//! draw raw `u32` values from the PRNG with [`RngCore::next_u32()`].
+//!
+//! They do not rely on `rand` distributions, so seeded outcomes only depend on
+//! the PRNG output stream, and stay stable across `rand` versions.
+//!
+
+use std::ops::{Range, RangeInclusive};
+
+use rand::RngCore;
+
+/// Draw an index in `0..len`.
+///
+/// Uses a widening multiply, `len` must be lower than `u32::MAX`.
+///
+pub fn index<R>(rng: &mut R, len: usize) -> usize
+where
+    R: RngCore + ?Sized,
+{
+    ((rng.next_u32() as u64 * len as u64) >> 32) as usize
+}
+
+/// Draw a float in `0.0..1.0`, with 24 bits of precision.
+///
+pub fn unit<R>(rng: &mut R) -> f32
+where
+    R: RngCore + ?Sized,
+{
+    (rng.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// Draw a float in the given range.
+///
+pub fn range_f32<R>(rng: &mut R, range: Range<f32>) -> f32
+where
+    R: RngCore + ?Sized,
+{
+    range.start + unit(rng) * (range.end - range.start)
+}
+
+/// Draw an integer in the given inclusive range.
+///
+/// Panics if the range is empty.
+///
+pub fn range_u32<R>(rng: &mut R, range: RangeInclusive<u32>) -> u32
+where
+    R: RngCore + ?Sized,
+{
+    let (start, end) = range.into_inner();
+    assert!(start <= end, "cannot pick in an empty range");
+
+    let span = (end - start) as u64 + 1;
+    start + ((rng.next_u32() as u64 * span) >> 32) as u32
+}
+
+/// Draw a float in `0.0..=1.0` from a Beta(a, b) distribution, as the ratio of two gamma draws.
+///
+/// Always terminates, whatever the parameters. Both `a` and `b` must be positive and finite.
+///
+pub fn beta<R>(rng: &mut R, a: f32, b: f32) -> f32
+where
+    R: RngCore + ?Sized,
+{
+    let x = ln_gamma(rng, a as f64);
+    let y = ln_gamma(rng, b as f64);
+
+    // x / (x + y), from the logarithms so that tiny parameters do not underflow to 0 / 0
+    (1.0 / (1.0 + (y - x).exp())) as f32
+}
+
+/// Draw the logarithm of a Gamma(shape, 1) value, with the Marsaglia-Tsang method.
+///
+/// Shapes below 1 are boosted to `shape + 1`, then scaled back by `u^(1 / shape)`.
+///
+fn ln_gamma<R>(rng: &mut R, shape: f64) -> f64
+where
+    R: RngCore + ?Sized,
+{
+    if shape < 1.0 {
+        return ln_gamma(rng, shape + 1.0) + open_unit(rng).ln() / shape;
+    }
+
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+
+    // each attempt is accepted with a probability above 0.95, the cap is never reached in practice
+    for _ in 0..MAX_GAMMA_ATTEMPTS {
+        let z = normal(rng);
+        let v = (1.0 + c * z).powi(3);
+
+        if v > 0.0 && open_unit(rng).ln() < 0.5 * z * z + d - d * v + d * v.ln() {
+            return (d * v).ln();
+        }
+    }
+
+    d.ln()
+}
+
+const MAX_GAMMA_ATTEMPTS: usize = 64;
+
+/// Draw a float from a standard normal distribution, with the Box-Muller transform.
+///
+fn normal<R>(rng: &mut R) -> f64
+where
+    R: RngCore + ?Sized,
+{
+    let radius = (-2.0 * open_unit(rng).ln()).sqrt();
+    let angle = 2.0 * std::f64::consts::PI * open_unit(rng);
+
+    radius * angle.cos()
+}
+
+/// Draw a float in `0.0..1.0`, both bounds excluded.
+///
+fn open_unit<R>(rng: &mut R) -> f64
+where
+    R: RngCore + ?Sized,
+{
+    ((rng.next_u32() >> 8) as f64 + 0.5) / (1u32 << 24) as f64
+}
+
+/// Pick a reference to one element of a slice.
+///
+/// Returns `None` on empty slices, without drawing from the PRNG
+///
+pub fn choose<'s, T, R>(rng: &mut R, slice: &'s [T]) -> Option<&'s T>
+where
+    R: RngCore + ?Sized,
+{
+    match slice.len() {
+        0 => None,
+        len => Some(&slice[index(rng, len)]),
+    }
+}
+
+/// Pick an index, with a probability proportional to its weight.
+///
+/// Returns `None`, without drawing from the PRNG, if a weight is negative or not finite,
+/// or if all weights are zero
+///
+pub fn weighted<R, I>(rng: &mut R, weights: I) -> Option<usize>
+where
+    R: RngCore + ?Sized,
+    I: IntoIterator<Item = f32>,
+{
+    let weights: Vec<f32> = weights.into_iter().collect();
+
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+        return None;
+    }
+
+    let total: f32 = weights.iter().sum();
+    if total <= 0.0 || !total.is_finite() {
+        return None;
+    }
+
+    let target = unit(rng) * total;
+    let mut cumulative = 0.0;
+
+    for (i, weight) in weights.iter().enumerate() {
+        cumulative += weight;
+        if target < cumulative {
+            return Some(i);
+        }
+    }
+
+    // rounding errors may leave the target past the last cumulated weight
+    weights.iter().rposition(|w| *w > 0.0)
+}
+
+/// Pick an index, with a probability proportional to its integer weight.
+///
+/// With equal weights, this draws the same index as [`choose()`].
+///
+/// Returns `None`, without drawing from the PRNG, if all weights are zero
+///
+pub fn weighted_u32<R, I>(rng: &mut R, weights: I) -> Option<usize>
+where
+    R: RngCore + ?Sized,
+    I: IntoIterator<Item = u32>,
+{
+    let weights: Vec<u64> = weights.into_iter().map(u64::from).collect();
+
+    let total: u64 = weights.iter().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let target = ((rng.next_u32() as u128 * total as u128) >> 32) as u64;
+    let mut cumulative = 0;
+
+    weights.iter().position(|weight| {
+        cumulative += weight;
+        target < cumulative
+    })
+}
+
+/// Shuffle a slice in place (Fisher-Yates).
+///
+pub fn shuffle<T, R>(rng: &mut R, slice: &mut [T])
+where
+    R: RngCore + ?Sized,
+{
+    for i in (1..slice.len()).rev() {
+        slice.swap(i, index(rng, i + 1));
+    }
+}
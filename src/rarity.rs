@@ -0,0 +1,60 @@
+//! Module containing rarity types used in Lootr.
+//!
+//! Rarities are ordered tiers an [`Item`](crate::item::Item) can optionally belong to,
+//! from [`Rarity::Common`] to [`Rarity::Legendary`].
+//!
+//! Items without a rarity are considered below any tier.
+//!
+//! Each tier maps to a drop chance, see [`Rarity::chance()`], which can be used as a drop luck.
+//!
+
+/// Holds a Lootr rarity tier.
+///
+/// Tiers are ordered, so `Rarity::Common < Rarity::Legendary`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    /// Holds all tiers, from the most common to the rarest.
+    ///
+    pub const ALL: [Rarity; 5] = [
+        Rarity::Common,
+        Rarity::Uncommon,
+        Rarity::Rare,
+        Rarity::Epic,
+        Rarity::Legendary,
+    ];
+
+    /// Return the drop chance of this tier, between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::{drops::DropBuilder, rarity::Rarity};
+    ///
+    /// let drop = DropBuilder::new()
+    ///     .luck(Rarity::Epic.chance())
+    ///     .rarity(Rarity::Epic)
+    ///     .build();
+    ///
+    /// assert_eq!(drop.luck, 0.1);
+    /// assert!(Rarity::Common.chance() > Rarity::Legendary.chance());
+    /// ```
+    pub fn chance(&self) -> f32 {
+        match self {
+            Rarity::Common => 1.0,
+            Rarity::Uncommon => 0.5,
+            Rarity::Rare => 0.25,
+            Rarity::Epic => 0.1,
+            Rarity::Legendary => 0.02,
+        }
+    }
+}
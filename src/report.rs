@@ -0,0 +1,48 @@
+//! Module containing report types used in Lootr.
+//!
+//! Reports summarize how a drop table behaves against a lootbag, by sampling it.
+//!
+//! A [`DropReport`](crate::report::DropReport) is built with [`Lootr::drop_report()`](crate::Lootr::drop_report).
+//!
+
+use std::{collections::BTreeMap, fmt};
+
+/// Holds the sampled statistics of a drop table.
+///
+/// All values are empirical, computed over `samples` loots.
+///
+#[derive(Debug, Clone, Default)]
+pub struct DropReport {
+    /// Holds the number of sampled loots.
+    ///
+    pub samples: usize,
+
+    /// Holds the average number of items yield by a loot.
+    ///
+    pub expected_items: f64,
+
+    /// Holds, for each item name, the probability to find it in a loot.
+    ///
+    pub item_probabilities: BTreeMap<String, f64>,
+
+    /// Holds, for each drop, the average stack size when the drop yields something.
+    ///
+    pub expected_stacks: Vec<f64>,
+}
+
+impl fmt::Display for DropReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "samples: {}", self.samples)?;
+        writeln!(f, "expected items per roll: {:.3}", self.expected_items)?;
+
+        for (i, stack) in self.expected_stacks.iter().enumerate() {
+            writeln!(f, "drop #{i}: expected stack {stack:.3}")?;
+        }
+
+        for (name, probability) in &self.item_probabilities {
+            writeln!(f, "{name}: {:.2}%", probability * 100.0)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! Module containing salvage helpers used in Lootr.
+//!
+//! Salvaging is the inverse of looting: an [`Item`](crate::item::Item) is broken down
+//! into component items, as described by some [`SalvageRules`](crate::salvage::SalvageRules).
+//!
+//! The easiest way to salvage an item is to use [`salvage()`](crate::salvage::salvage).
+//!
+
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+use crate::{item::Item, pick};
+
+/// Holds what an item must carry to match a salvage rule.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SalvageKey<'a> {
+    /// The item must have this property.
+    ///
+    Prop(&'a str),
+
+    /// The item must have this tag.
+    ///
+    Tag(&'a str),
+}
+
+impl SalvageKey<'_> {
+    /// Check if the given item matches this key.
+    ///
+    pub fn matches(&self, item: &Item) -> bool {
+        match self {
+            SalvageKey::Prop(key) => item.has_prop(key),
+            SalvageKey::Tag(tag) => item.has_tag(tag),
+        }
+    }
+}
+
+/// Holds a single salvage rule.
+///
+/// Items matching the `key` property or tag yield `quantity` copies of `component`.
+///
+#[derive(Debug, Clone)]
+pub struct SalvageRule<'a> {
+    /// Holds the property or tag an item must have to match this rule.
+    ///
+    pub key: SalvageKey<'a>,
+
+    /// Holds the component item yield by this rule.
+    ///
+    pub component: Item<'a>,
+
+    /// Holds the range of component copies to yield.
+    ///
+    pub quantity: RangeInclusive<u32>,
+}
+
+/// Holds a set of salvage rules.
+///
+#[derive(Debug, Clone, Default)]
+pub struct SalvageRules<'a> {
+    /// Holds the rules, applied in order.
+    ///
+    pub rules: Vec<SalvageRule<'a>>,
+}
+
+impl<'a> SalvageRules<'a> {
+    /// Create an empty rule set.
+    ///
+    pub fn new() -> Self {
+        Self { rules: vec![] }
+    }
+
+    /// Add a rule, returning the rule set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::{item::Item, salvage::SalvageRules};
+    ///
+    /// let rules = SalvageRules::new()
+    ///     .rule("attack", Item::a("metal"), 1..=3)
+    ///     .rule("magic_power", Item::a("crystal"), 1..=1);
+    ///
+    /// assert_eq!(rules.rules.len(), 2);
+    /// ```
+    pub fn rule(
+        mut self,
+        key: &'a str,
+        component: Item<'a>,
+        quantity: RangeInclusive<u32>,
+    ) -> Self {
+        self.rules.push(SalvageRule {
+            key: SalvageKey::Prop(key),
+            component,
+            quantity,
+        });
+        self
+    }
+
+    /// Add a rule matching items by tag, returning the rule set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::{item::Item, salvage::{SalvageKey, SalvageRules}};
+    ///
+    /// let rules = SalvageRules::new().tag_rule("wooden", Item::a("plank"), 1..=2);
+    ///
+    /// assert_eq!(rules.rules[0].key, SalvageKey::Tag("wooden"));
+    /// ```
+    pub fn tag_rule(
+        mut self,
+        tag: &'a str,
+        component: Item<'a>,
+        quantity: RangeInclusive<u32>,
+    ) -> Self {
+        self.rules.push(SalvageRule {
+            key: SalvageKey::Tag(tag),
+            component,
+            quantity,
+        });
+        self
+    }
+}
+
+/// Break an item down into components, given some rules and a PRNG.
+///
+/// Every matching rule yields its component, in rules order.
+///
+/// # Examples
+///
+/// ```
+/// use lootr::{item::{Item, Props}, salvage::{salvage, SalvageRules}};
+/// use rand_chacha::ChaCha20Rng;
+/// use rand::SeedableRng;
+///
+/// let rules = SalvageRules::new().rule("attack", Item::a("metal"), 2..=2);
+/// let sword = Item::from("sword", Props::from([("attack", "10")]));
+///
+/// let components = salvage(&sword, &rules, &mut ChaCha20Rng::seed_from_u64(123));
+///
+/// assert_eq!(components.len(), 2);
+/// ```
+pub fn salvage<'a, R>(item: &Item, rules: &SalvageRules<'a>, rng: &mut R) -> Vec<Item<'a>>
+where
+    R: Rng + ?Sized,
+{
+    let mut components = vec![];
+
+    for rule in rules.rules.iter().filter(|rule| rule.key.matches(item)) {
+        let quantity = pick::range_u32(rng, rule.quantity.clone());
+        components.extend((0..quantity).map(|_| rule.component.clone()));
+    }
+
+    components
+}
@@ -0,0 +1,108 @@
+//! Module containing property schema types used in Lootr.
+//!
+//! A [`PropSchema`](crate::schema::PropSchema) describes the expected type of some item properties.
+//! Items are checked against it with [`Item::validate_props()`](crate::item::Item::validate_props).
+//!
+
+use std::{collections::BTreeMap, fmt};
+
+/// Holds an expected property type.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropType {
+    /// The value parses as an `i64`.
+    Int,
+
+    /// The value parses as an `f64`.
+    Float,
+
+    /// Any value.
+    String,
+}
+
+impl PropType {
+    /// Check if the given value conforms to this type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::schema::PropType;
+    ///
+    /// assert_eq!(PropType::Int.accepts("+10"), true);
+    /// assert_eq!(PropType::Int.accepts("1.5"), false);
+    /// assert_eq!(PropType::Float.accepts("1.5"), true);
+    /// ```
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            PropType::Int => value.parse::<i64>().is_ok(),
+            PropType::Float => value.parse::<f64>().is_ok(),
+            PropType::String => true,
+        }
+    }
+}
+
+/// Holds the expected types of item properties, by key.
+///
+/// Properties missing from an item are not reported.
+///
+#[derive(Debug, Clone, Default)]
+pub struct PropSchema<'a> {
+    /// Holds the expected type of each property.
+    ///
+    pub props: BTreeMap<&'a str, PropType>,
+}
+
+impl<'a> PropSchema<'a> {
+    /// Create an empty schema.
+    ///
+    pub fn new() -> Self {
+        Self {
+            props: BTreeMap::new(),
+        }
+    }
+
+    /// Add an expected property type, returning the schema.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lootr::schema::{PropSchema, PropType};
+    ///
+    /// let schema = PropSchema::new()
+    ///     .prop("attack", PropType::Int)
+    ///     .prop("desc", PropType::String);
+    /// ```
+    pub fn prop(mut self, key: &'a str, expected: PropType) -> Self {
+        self.props.insert(key, expected);
+        self
+    }
+}
+
+/// Holds a property which does not conform to its schema.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropError {
+    /// Holds the property key.
+    ///
+    pub key: String,
+
+    /// Holds the expected type.
+    ///
+    pub expected: PropType,
+
+    /// Holds the actual value.
+    ///
+    pub value: String,
+}
+
+impl fmt::Display for PropError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected {:?}, got {:?}",
+            self.key, self.expected, self.value
+        )
+    }
+}
+
+impl std::error::Error for PropError {}
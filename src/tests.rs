@@ -1,14 +1,26 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::{
         bag,
-        drops::{Drop, DropBuilder},
+        cooldown::CooldownTracker,
+        drops::{Drop, DropBuilder, DropSet, ParseError},
+        fair::FairBag,
         item::Props,
-        Item, Lootr, ROOT,
+        modifiers,
+        rarity::Rarity,
+        salvage::{salvage, SalvageRules},
+        schema::{PropError, PropSchema, PropType},
+        Item, Lootr, LootrError, RenderOptions, RollProfile, ThresholdDist, ROOT,
     };
     use rand::SeedableRng;
     use rand_chacha::ChaCha20Rng;
-    use std::{collections::HashMap, fmt};
+    use std::{
+        borrow::Cow,
+        collections::{HashMap, HashSet},
+        fmt,
+        time::{Duration, Instant},
+    };
 
     #[test]
     fn success_item() {
@@ -17,7 +29,7 @@ mod tests {
             Props::from([("strength", "10"), ("charisma", "+100")]),
         );
 
-        assert_eq!(item.has_prop("strength"), true);
+        assert!(item.has_prop("strength"));
         assert_eq!(item.get_prop("strength").unwrap(), "10");
     }
 
@@ -29,13 +41,243 @@ mod tests {
         );
         // println!("{}", item);
         let output = fmt::format(format_args!("{}", item));
-        assert_eq!(
+        assert!(
             output == "crown{strength=10,charisma=+100}"
                 || output == "crown{charisma=+100,strength=10}",
-            true
         );
     }
 
+    #[test]
+    fn success_item_with_modified_prop() {
+        let sword = Item::from("sword", Props::from([("attack", "10"), ("cursed", "yes")]));
+
+        let blessed = sword
+            .with_modified_prop("attack", |attack| {
+                attack
+                    .and_then(|attack| attack.parse::<i32>().ok())
+                    .map(|attack| (attack + 1).to_string())
+            })
+            .with_modified_prop("cursed", |_| None::<&str>);
+
+        assert_eq!(blessed.get_prop("attack"), Some("11"));
+        assert!(!blessed.has_prop("cursed"));
+        assert_eq!(sword.get_prop("attack"), Some("10"));
+    }
+
+    #[test]
+    fn success_approx_heap_size() {
+        let empty = Item::a("crown");
+        let item = Item::from(
+            "crown",
+            Props::from([("strength", "10"), ("charisma", "+100")]),
+        );
+
+        assert!(item.approx_heap_size() > empty.approx_heap_size());
+
+        let loot = stuffed();
+        let mut bigger = stuffed();
+        bigger.add_in(item, "weapons");
+
+        assert!(loot.approx_heap_size() > 0);
+        assert!(bigger.approx_heap_size() > loot.approx_heap_size());
+    }
+
+    #[test]
+    fn success_item_format_with() {
+        let axe = Item::from("Axe", Props::from([("attack", "12")]));
+
+        assert_eq!(
+            axe.format_with("{name} (ATK {prop:attack})"),
+            "Axe (ATK 12)"
+        );
+        assert_eq!(axe.format_with("{name} (DEF {prop:defense})"), "Axe (DEF )");
+        assert_eq!(axe.format_with("{other} {name"), "{other} {name");
+        assert_eq!(Item::a("Rock").format_with("[{prop:attack}]"), "[]");
+    }
+
+    #[test]
+    fn success_item_deep_clone() {
+        let hat = Item::from("hat", Props::from([("color", "black")]));
+        let copy = hat.deep_clone();
+
+        assert_eq!(copy, hat);
+
+        assert!(matches!(copy.name, Cow::Owned(_)));
+        assert!(copy
+            .props
+            .as_ref()
+            .unwrap()
+            .0
+            .iter()
+            .all(|(k, v)| matches!((k, v), (Cow::Owned(_), Cow::Owned(_)))));
+        assert_ne!(
+            hat.get_prop("color").unwrap().as_ptr(),
+            copy.get_prop("color").unwrap().as_ptr(),
+            "Should copy the borrowed strings"
+        );
+
+        let clone = hat.clone();
+        assert!(matches!(clone.name, Cow::Borrowed(_)));
+
+        assert_eq!(Item::a("cap").deep_clone().props, None);
+    }
+
+    #[test]
+    fn success_item_get_prop_or() {
+        let boots = Item::from("boots", Props::from([("agility", "3")]));
+
+        assert_eq!(boots.get_prop_or("agility", "0"), "3");
+        assert_eq!(boots.get_prop_or("strength", "0"), "0");
+        assert_eq!(Item::a("rock").get_prop_or("agility", "0"), "0");
+    }
+
+    #[test]
+    fn success_item_props_iter() {
+        let boots = Item::from("boots", Props::from([("agility", "3"), ("defense", "2")]));
+
+        let mut props: Vec<(&str, &str)> = boots.props_iter().collect();
+        props.sort();
+
+        assert_eq!(props, vec![("agility", "3"), ("defense", "2")]);
+        assert_eq!(Item::a("rock").props_iter().count(), 0);
+    }
+
+    #[test]
+    fn success_item_get_prop_parsed() {
+        let potion = Item::from(
+            "potion",
+            Props::from([("healing", "15"), ("mana", "+100"), ("color", "blue")]),
+        );
+
+        assert_eq!(potion.get_prop_parsed::<i32>("healing"), Some(15));
+        assert_eq!(potion.get_prop_parsed::<i32>("mana"), Some(100));
+        assert_eq!(potion.get_prop_parsed::<f32>("healing"), Some(15.0));
+        assert_eq!(potion.get_prop_parsed::<i32>("color"), None);
+        assert_eq!(potion.get_prop_parsed::<i32>("weight"), None);
+
+        assert_eq!(potion.get_prop_as::<i32>("mana"), Some(Ok(100)));
+        assert!(matches!(potion.get_prop_as::<i32>("color"), Some(Err(_))));
+        assert!(potion.get_prop_as::<i32>("weight").is_none());
+    }
+
+    #[test]
+    fn success_item_prop_list() {
+        let cloak = Item::from(
+            "cloak",
+            Props::from([("resistances", "fire, ice,,poison"), ("color", "red")]),
+        );
+
+        assert_eq!(
+            cloak.get_prop_list("resistances", ','),
+            vec!["fire", "ice", "poison"]
+        );
+        assert_eq!(cloak.get_prop_list("color", ','), vec!["red"]);
+        assert!(cloak.get_prop_list("weight", ',').is_empty());
+
+        assert!(cloak.has_prop_value("resistances", "poison", ','));
+        assert!(!cloak.has_prop_value("resistances", "acid", ','));
+        assert!(!cloak.has_prop_value("weight", "fire", ','));
+    }
+
+    #[test]
+    fn success_item_validate_props() {
+        let schema = PropSchema::new()
+            .prop("attack", PropType::Int)
+            .prop("speed", PropType::Float)
+            .prop("desc", PropType::String);
+
+        let sword = Item::from(
+            "sword",
+            Props::from([("attack", "+10"), ("speed", "1.5"), ("desc", "Sharp")]),
+        );
+        assert!(sword.validate_props(&schema).is_empty());
+
+        let broken = Item::from("broken", Props::from([("attack", "sharp"), ("speed", "2")]));
+        let errors = broken.validate_props(&schema);
+
+        assert_eq!(
+            errors,
+            vec![PropError {
+                key: String::from("attack"),
+                expected: PropType::Int,
+                value: String::from("sharp"),
+            }]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn success_serde_json_round_trip() {
+        let loot = stuffed();
+
+        let json = serde_json::to_string(&loot).unwrap();
+        let restored: Lootr = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.all_count(), loot.all_count());
+        assert_eq!(restored.to_string(), loot.to_string());
+
+        let authored: Lootr = serde_json::from_str(
+            r#"{
+                "items": [{ "name": "Staff" }],
+                "branchs": {
+                    "weapons": { "items": [{ "name": "Uzi", "props": { "ammo": "9mm" }, "weight": 3 }] }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let uzi = &authored.branch("weapons").unwrap().items()[0];
+        assert_eq!(authored.all_count(), 2);
+        assert_eq!(uzi.get_prop("ammo"), Some("9mm"));
+        assert_eq!(uzi.weight, 3);
+        assert_eq!(authored.items()[0].weight, 1);
+        assert_eq!(authored.threshold_dist, ThresholdDist::Uniform);
+        assert!(!authored.sorted_modifiers && !authored.merge_on_add);
+
+        let drop: Drop = serde_json::from_str(r#"{ "path": "weapons", "luck": 0.5 }"#).unwrap();
+        assert_eq!(drop.path, Some("weapons"));
+        assert_eq!(drop.luck, 0.5);
+        assert_eq!(drop.stack, 1..=1);
+    }
+
+    #[test]
+    #[cfg(feature = "bincode")]
+    fn success_bincode_round_trip() {
+        let mut loot = stuffed();
+        loot.add_in(
+            Item::from("Saber", Props::from([("attack", "12"), ("edge", "sharp")])),
+            "weapons",
+        );
+
+        let bytes = loot.to_bincode().unwrap();
+        let restored = Lootr::from_bincode(&bytes).unwrap();
+
+        assert_eq!(restored.all_count(), loot.all_count());
+        assert_eq!(
+            restored.branch("weapons").unwrap().items(),
+            loot.branch("weapons").unwrap().items()
+        );
+
+        let saber = restored.branch("weapons").unwrap().items().last().unwrap();
+        assert_eq!(saber.get_prop("attack"), Some("12"));
+        assert_eq!(saber.get_prop("edge"), Some("sharp"));
+    }
+
+    #[test]
+    fn success_build() {
+        let loot = Lootr::build(|loot| {
+            loot.add(Item::a("Staff"))
+                .add_branch("weapons", Lootr::from(vec![Item::a("Bat"), Item::a("Uzi")]))
+                .add_branch("equipment", Lootr::new());
+            loot.add_in(Item::a("Gloves"), "equipment")
+                .add_in(Item::a("Boots"), "equipment");
+        });
+
+        assert_eq!(loot.all_count(), 5);
+        assert_eq!(loot.self_count_in("weapons"), Some(2));
+        assert_eq!(loot.self_count_in("equipment"), Some(2));
+    }
+
     #[test]
     fn success_from() {
         let loot = Lootr::from(vec![Item::a("Staff"), Item::an("Uzi")]);
@@ -103,7 +345,143 @@ mod tests {
         };
 
         println!("{}", loot);
-        assert_eq!(loot.all_count(), 29);
+        assert_eq!(loot.all_count(), 28);
+        assert_eq!(loot.self_count(), 0);
+        assert!(!loot.contains_item("test"));
+    }
+
+    #[test]
+    fn success_bag_macro_root_items() {
+        let loot = bag! {
+            Gold amount="10",
+            Map,
+            @Weapons
+                Knife attack="1" desc="A simple knife",
+                .
+        };
+
+        assert_eq!(loot.all_count(), 3);
+        assert_eq!(loot.self_count(), 2);
+        assert!(!loot.contains_item("test"));
+
+        let gold = &loot.items()[0];
+        assert_eq!(gold.name, "Gold");
+        assert_eq!(gold.get_prop("amount"), Some("10"));
+        assert_eq!(loot.items()[1].props, None);
+
+        let knife = &loot.branch("Weapons").unwrap().items()[0];
+        assert_eq!(knife.get_prop("desc"), Some("A simple knife"));
+        assert_eq!(knife.get_prop("attack"), Some("1"));
+    }
+
+    #[test]
+    fn success_bag_macro_bare_values() {
+        let loot = bag! {
+            Sword attack=1 bonus=-2 element=fire,
+        };
+
+        let sword = &loot.items()[0];
+        assert_eq!(sword.get_prop("attack"), Some("1"));
+        assert_eq!(sword.get_prop("bonus"), Some("-2"));
+        assert_eq!(sword.get_prop("element"), Some("fire"));
+    }
+
+    #[test]
+    fn success_remove_branch() {
+        let mut loot = stuffed();
+
+        let scraps = loot.remove_branch("equipment/leather/Scraps").unwrap();
+
+        assert_eq!(scraps.self_count(), 2);
+        assert_eq!(loot.all_count(), 7);
+        assert!(loot.branch("equipment/leather/Scraps").is_none());
+        assert_eq!(loot.self_count_in("equipment/leather"), Some(2));
+
+        let weapons = loot.remove_branch("/weapons/").unwrap();
+
+        assert_eq!(weapons.all_count(), 2);
+        assert_eq!(loot.all_count(), 5);
+        assert!(loot.remove_branch("weapons").is_none());
+        assert!(loot.remove_branch("armors/heavy").is_none());
+    }
+
+    #[test]
+    fn success_remove_matching() {
+        let mut loot = stuffed();
+        loot.add(Item::a("Scroll")).add(Item::a("Stick"));
+
+        let removed = loot.remove_matching(|item| item.name.starts_with('S'));
+
+        assert_eq!(
+            removed.iter().map(|item| &item.name).collect::<Vec<_>>(),
+            vec!["Staff", "Scroll", "Stick"]
+        );
+        assert_eq!(loot.self_count(), 0);
+        assert_eq!(loot.all_count(), 8);
+    }
+
+    #[test]
+    fn success_set_prop_on_all() {
+        let mut loot = stuffed();
+        loot.add(Item::from("Mask", Props::from([("event", "carnival")])));
+
+        loot.set_prop_on_all("event", "halloween");
+
+        let items = loot.all_items();
+        assert_eq!(items.len(), 10);
+        assert!(items
+            .iter()
+            .all(|item| item.get_prop("event") == Some("halloween")));
+    }
+
+    #[test]
+    fn success_remove_item_everywhere() {
+        let mut loot = stuffed();
+        loot.add_in(Item::a("Junk"), "weapons")
+            .add_in(Item::a("Junk"), "equipment/leather/Scraps");
+
+        assert!(loot.contains_item("Junk"));
+        assert_eq!(loot.remove_item_everywhere("Junk"), 2);
+        assert!(!loot.contains_item("Junk"));
+        assert!(loot.contains_item("Patch"));
+        assert_eq!(loot.all_count(), 9);
+        assert_eq!(loot.remove_item_everywhere("Junk"), 0);
+    }
+
+    #[test]
+    fn success_prune_items_matching() {
+        let mut loot = bag! {
+            @Armors
+                Shirt defense="0" desc="A simple shirt",
+                @LightArmor
+                    LeatherArmor defense="5" agility="2" desc="Armor made of leather with increased agility",
+                    Rags defense="0" desc="Barely armor",
+                    .
+                .
+        };
+        let before = loot.all_count();
+
+        let pruned = loot.prune_items_matching(|item| item.get_prop("defense") == Some("0"));
+
+        assert_eq!(pruned, 2);
+        assert_eq!(loot.all_count(), before - 2);
+        assert_eq!(loot.branch("Armors/LightArmor").unwrap().self_count(), 1);
+    }
+
+    #[test]
+    fn success_trim() {
+        let mut loot = stuffed();
+        let names = ["A", "B", "C", "D", "E", "F", "G", "H"];
+        names.iter().for_each(|name| {
+            loot.add_in(Item::a(name), "weapons");
+        });
+
+        let removed = loot.trim(3, &mut ChaCha20Rng::seed_from_u64(123));
+
+        assert_eq!(removed, 7);
+        assert_eq!(loot.self_count_in("weapons"), Some(3));
+        assert_eq!(loot.self_count_in("equipment"), Some(2));
+        assert_eq!(loot.all_count(), 10);
     }
 
     #[test]
@@ -113,6 +491,132 @@ mod tests {
         assert_eq!(output.split("─").count(), 10);
     }
 
+    #[test]
+    fn success_branch_render() {
+        let mut loot = stuffed();
+        loot.add(Item::from("Crown", Props::from([("charisma", "+100")])));
+
+        let output = loot.render(RenderOptions {
+            show_props: false,
+            show_counts: true,
+            max_depth: None,
+        });
+
+        assert!(!output.contains('{'), "Should hide props");
+        assert!(output.contains("Crown"));
+        assert!(output.contains("ROOT (10)"));
+        assert!(output.contains("equipment (6)"));
+        assert!(output.contains("Scraps (2)"));
+
+        let output = loot.render(RenderOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        });
+
+        assert!(output.contains("Crown{charisma=+100}"), "Should show props");
+        assert!(output.contains("equipment"));
+        assert!(!output.contains("leather"), "Should stop at depth 1");
+        assert_eq!(loot.render(RenderOptions::default()), format!("{}", loot));
+    }
+
+    #[test]
+    fn success_all_paths() {
+        let loot = stuffed();
+
+        assert_eq!(
+            loot.all_paths(),
+            vec![
+                "equipment",
+                "equipment/leather",
+                "equipment/leather/Scraps",
+                "weapons",
+            ]
+        );
+        assert!(Lootr::new().all_paths().is_empty());
+
+        assert!(loot.has_branch("equipment/leather"));
+        assert!(loot.has_branch("/weapons/"));
+        assert!(!loot.has_branch("equipment/cotton"));
+        assert!(loot.all_paths().iter().all(|path| loot.has_branch(path)));
+    }
+
+    #[test]
+    fn success_iter_detailed() {
+        let loot = stuffed();
+
+        let detailed: Vec<(i16, String, &Item)> = loot.iter_detailed().collect();
+
+        assert_eq!(detailed.len(), loot.all_count());
+        assert_eq!(detailed[0].0, 0);
+        assert_eq!(detailed[0].1, "");
+        assert_eq!(detailed[0].2.name, "Staff");
+
+        let (depth, path, _) = detailed
+            .iter()
+            .find(|(_, _, item)| item.name == "Patch")
+            .unwrap();
+
+        assert_eq!(*depth, 3);
+        assert_eq!(path, "equipment/leather/Scraps");
+    }
+
+    #[test]
+    fn success_iter_all() {
+        let loot = stuffed();
+
+        let paths: Vec<String> = loot.iter_all().map(|(path, _)| path).collect();
+
+        assert_eq!(paths.len(), loot.all_count());
+        assert_eq!(
+            paths,
+            vec![
+                "Staff",
+                "equipment/Gloves",
+                "equipment/Boots",
+                "equipment/leather/Jacket",
+                "equipment/leather/Pads",
+                "equipment/leather/Scraps/ArmBand",
+                "equipment/leather/Scraps/Patch",
+                "weapons/Bat",
+                "weapons/Uzi",
+            ]
+        );
+
+        let weapons = loot
+            .iter_all()
+            .filter(|(path, _)| path.starts_with("weapons"))
+            .map(|(_, item)| item.name.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(weapons, vec!["Bat", "Uzi"]);
+    }
+
+    #[test]
+    fn success_to_dot() {
+        let mut loot = stuffed();
+
+        let dot = loot.to_dot();
+
+        assert!(dot.starts_with("digraph lootr {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(
+            dot.matches("->").count(),
+            4 + 9,
+            "Should link every branch and item"
+        );
+        for name in ["ROOT", "weapons", "equipment", "leather", "Scraps"] {
+            assert!(dot.contains(&format!("[label=\"{name}\", shape=box]")));
+        }
+
+        loot.add(Item::from(
+            "Bob's \"Axe\"",
+            Props::from([("path", "C:\\axes"), ("attack", "12")]),
+        ));
+
+        assert!(loot
+            .to_dot()
+            .contains(r#"[label="Bob's \"Axe\"\nattack=12\npath=C:\\axes"];"#));
+    }
+
     #[test]
     fn success_add_item() {
         let mut loot = Lootr::new();
@@ -122,6 +626,66 @@ mod tests {
         assert_eq!(loot.self_count(), 1);
     }
 
+    #[test]
+    fn success_add_merged_item() {
+        let mut loot = Lootr::new();
+
+        loot.add(Item::a("Sword")).add(Item::a("Sword"));
+        assert_eq!(loot.self_count(), 2, "Should append by default");
+
+        let mut loot = Lootr::new();
+        loot.merge_on_add(true)
+            .add(Item::from(
+                "Sword",
+                Props::from([("attack", "10"), ("desc", "Rusty")]),
+            ))
+            .add(Item::from("Sword", Props::from([("attack", "12")])));
+
+        assert_eq!(loot.self_count(), 1);
+        assert_eq!(loot.items()[0].get_prop("attack"), Some("12"));
+        assert_eq!(loot.items()[0].get_prop("desc"), Some("Rusty"));
+    }
+
+    #[test]
+    fn success_add_routed() {
+        let mut loot = stuffed();
+
+        loot.add_routed(
+            Item::from("Sword", Props::from([("category", "weapons")])),
+            "category",
+        )
+        .add_routed(
+            Item::from("Apple", Props::from([("category", "food")])),
+            "category",
+        )
+        .add_routed(Item::a("Pebble"), "category");
+
+        assert_eq!(loot.self_count_in("weapons"), Some(3));
+        assert_eq!(loot.self_count_in("food"), Some(1));
+        assert_eq!(loot.self_count(), 2, "Should keep unrouted items at root");
+    }
+
+    #[test]
+    fn success_add_routed_owned() {
+        let mut loot = Lootr::new();
+
+        for n in 0..3 {
+            let props = HashMap::from([("tier".to_string(), format!("tier{n}"))]);
+            loot.add_routed(Item::owned(format!("Gem+{n}"), props), "tier");
+        }
+        loot.add_branch(format!("tier{}", 3), Lootr::from(vec![Item::a("Shard")]));
+
+        assert_eq!(
+            loot.self_count(),
+            0,
+            "Should route owned values to new branchs"
+        );
+        assert_eq!(loot.branchs().len(), 4);
+        assert_eq!(loot.self_count_in("tier1"), Some(1));
+        assert_eq!(loot.branch("tier1").unwrap().items()[0].name, "Gem+1");
+        assert_eq!(loot.self_count_in("tier3"), Some(1));
+    }
+
     #[test]
     fn success_add_branch() {
         let mut loot = Lootr::new();
@@ -154,6 +718,48 @@ mod tests {
         assert_eq!(fire_branch.unwrap().self_count(), 1);
     }
 
+    #[test]
+    fn success_branches_at_depth() {
+        let loot = stuffed();
+
+        assert_eq!(loot.branches_at_depth(0), 0);
+        assert_eq!(loot.branches_at_depth(1), 2);
+        assert_eq!(loot.branches_at_depth(2), 1);
+        assert_eq!(loot.branches_at_depth(3), 1);
+        assert_eq!(loot.branches_at_depth(4), 0);
+    }
+
+    #[test]
+    fn success_self_count_in() {
+        let loot = stuffed();
+
+        assert_eq!(loot.self_count_in("equipment"), Some(2));
+        assert_eq!(loot.self_count_in("equipment/leather"), Some(2));
+        assert_eq!(loot.self_count_in("food"), None);
+    }
+
+    #[test]
+    fn success_count_under_prefix() {
+        let loot = stuffed();
+
+        assert_eq!(loot.count_under_prefix("equipment"), 6);
+        assert_eq!(loot.count_under_prefix("/equipment/leather"), 4);
+        assert_eq!(loot.count_under_prefix("weapons"), 2);
+        assert_eq!(loot.count_under_prefix(""), loot.all_count());
+    }
+
+    #[test]
+    fn success_count_under_prefix_sibling_names() {
+        let mut loot = stuffed();
+        loot.add_branch("weapons_old", Lootr::from(vec![Item::a("Club")]));
+        loot.add_in(Item::a("Sling"), "weapons_old/ranged");
+
+        assert_eq!(loot.count_under_prefix("weapons"), 2);
+        assert_eq!(loot.count_under_prefix("weapons_old"), 2);
+        assert_eq!(loot.count_under_prefix("weapons_old/ranged"), 1);
+        assert_eq!(loot.count_under_prefix("equip"), 0);
+    }
+
     #[test]
     fn success_add_item_in_branch() {
         let mut loot = Lootr::new();
@@ -168,228 +774,2096 @@ mod tests {
     }
 
     #[test]
-    fn success_get_all_items() {
-        let mut loot = Lootr::from(vec![Item::a("Staff")]);
+    fn success_try_add_item_in_branch() {
+        let mut loot = stuffed();
 
-        loot.add_branch(
-            "weapons",
-            Lootr::from(vec![Item::a("Bat"), Item::an("Uzi")]),
-        );
+        assert!(loot
+            .try_add_in(Item::a("Belt"), "equipment/leather")
+            .is_ok());
+        assert_eq!(loot.self_count_in("equipment/leather"), Some(3));
 
-        assert_eq!(loot.all_items().len(), 3);
+        assert_eq!(
+            loot.try_add_in(Item::a("Belt"), "equipment/cotton").err(),
+            Some(LootrError::PathNotFound(String::from("cotton")))
+        );
+        assert_eq!(loot.all_count(), 10);
     }
 
     #[test]
-    fn success_roll_root() {
-        let loot = stuffed();
+    fn success_try_branch() {
+        let mut loot = stuffed();
+
+        assert!(loot.try_branch("equipment/leather/Scraps").is_ok());
+        assert!(loot.try_branch_mut("/weapons/").is_ok());
 
         assert_eq!(
-            loot.roll(ROOT, 0, 1.0).unwrap().name,
-            "Staff",
-            "Should return the only element of root"
+            loot.try_branch("weapons/unknown/thing").err(),
+            Some(LootrError::PathNotFound(String::from("unknown")))
         );
+        assert_eq!(
+            loot.try_branch_mut("armors").err(),
+            Some(LootrError::PathNotFound(String::from("armors")))
+        );
+        assert!(loot.branch("weapons/unknown/thing").is_none());
+        assert!(loot.branch_mut("weapons/unknown/thing").is_none());
     }
 
     #[test]
-    fn success_roll_any() {
+    fn success_resolve() {
         let loot = stuffed();
-        let picked = loot.roll_any().unwrap();
 
-        let expected = ["Staff", "Bat", "Uzi", "Gloves", "Boots", "Jacket", "Pads"];
-        assert_eq!(
-            expected.contains(&picked.name),
-            true,
-            "Should return any element"
-        );
+        let (branch, matched) = loot.resolve("equipment/leather/unknown/thing");
+        assert!(branch.is_none());
+        assert_eq!(matched, "equipment/leather");
+
+        let (branch, matched) = loot.resolve("/equipment/leather/");
+        assert_eq!(branch.map(|b| b.self_count()), Some(2));
+        assert_eq!(matched, "equipment/leather");
+
+        let (branch, matched) = loot.resolve("unknown");
+        assert!(branch.is_none());
+        assert_eq!(matched, "");
     }
 
     #[test]
-    fn success_roll_any_seeded() {
-        (1..9).for_each(|i| {
-            let loot = stuffed();
-            let first_picked = loot
-                .roll_seeded(
-                    ROOT,
-                    i16::MAX,
-                    1.0,
-                    &mut ChaCha20Rng::seed_from_u64(123 * i),
-                )
-                .unwrap();
+    fn success_ensure_branch() {
+        let mut loot = Lootr::new();
+
+        loot.ensure_branch("x/y/z").add(Item::a("Belt"));
+
+        assert_eq!(loot.branch("x/y/z").unwrap().items()[0].name, "Belt");
+
+        loot.ensure_branch("x/y").add(Item::a("Hat"));
+        loot.ensure_branch("/x/y/z/");
+
+        assert_eq!(loot.self_count_in("x/y"), Some(1));
+        assert_eq!(loot.self_count_in("x/y/z"), Some(1));
+        assert_eq!(loot.all_count(), 2);
+    }
+
+    #[test]
+    fn success_add_item_in_missing_branch() {
+        let mut loot = stuffed();
+
+        loot.add_in(Item::a("Belt"), "equipment/cotton/belts");
+
+        assert_eq!(loot.self_count_in("equipment/cotton/belts"), Some(1));
+        assert_eq!(loot.self_count_in("equipment"), Some(2));
+        assert_eq!(loot.all_count(), 10);
+    }
+
+    #[test]
+    fn success_merge() {
+        let mut loot = stuffed();
+
+        let mut dlc = Lootr::from(vec![Item::a("Wand")]);
+        dlc.add_branch("weapons", Lootr::from(vec![Item::a("Laser")]));
+        dlc.add_branch("potions", Lootr::from(vec![Item::a("Elixir")]));
+        dlc.add_modifier(|item| item);
+
+        loot.merge(dlc);
+
+        assert_eq!(loot.all_count(), 12);
+        assert_eq!(loot.self_count(), 2);
+        assert_eq!(loot.self_count_in("weapons"), Some(3));
+        assert_eq!(loot.self_count_in("potions"), Some(1));
+
+        loot.merge(stuffed());
+
+        assert_eq!(loot.all_count(), 12 + 9);
+        assert_eq!(loot.self_count_in("equipment/leather/Scraps"), Some(4));
+    }
+
+    #[test]
+    fn success_merge_at() {
+        let mut loot = stuffed();
+
+        loot.merge_at("equipment/leather", stuffed())
+            .merge_at("dlc/expansion", stuffed());
+
+        assert_eq!(loot.all_count(), 9 * 3);
+        assert_eq!(loot.self_count_in("equipment/leather"), Some(3));
+        assert_eq!(loot.self_count_in("equipment/leather/Scraps"), Some(2));
+        assert_eq!(loot.self_count_in("equipment/leather/weapons"), Some(2));
+        assert_eq!(loot.self_count_in("dlc/expansion/weapons"), Some(2));
+    }
+
+    #[test]
+    fn success_replace_contents() {
+        let mut loot = stuffed();
+
+        let mut reloaded = Lootr::from(vec![Item::a("Shield")]);
+        reloaded.add_branch("potions", Lootr::from(vec![Item::a("Elixir")]));
+
+        loot.replace_contents(reloaded);
+
+        assert_eq!(loot.all_count(), 2);
+        assert!(
+            loot.branch("weapons").is_none(),
+            "Old content should be gone"
+        );
+        assert_eq!(loot.items()[0].name, "Shield");
+        assert_eq!(loot.self_count_in("potions"), Some(1));
+    }
+
+    #[test]
+    fn success_get_all_items() {
+        let mut loot = Lootr::from(vec![Item::a("Staff")]);
+
+        loot.add_branch(
+            "weapons",
+            Lootr::from(vec![Item::a("Bat"), Item::an("Uzi")]),
+        );
+
+        assert_eq!(loot.all_items().len(), 3);
+    }
+
+    #[test]
+    fn success_find() {
+        let mut loot = stuffed();
+        loot.add(Item::from("Orb", Props::from([("magic_power", "15")])))
+            .add_in(
+                Item::from("Wand", Props::from([("magic_power", "5")])),
+                "weapons",
+            )
+            .add_in(
+                Item::from("Amulet", Props::from([("magic_power", "15")])),
+                "equipment/leather/Scraps",
+            );
+
+        let uzis = loot.find(|item| item.name == "Uzi");
+        assert_eq!(uzis.len(), 1);
+        assert!(std::ptr::eq(
+            uzis[0],
+            &loot.branch("weapons").unwrap().items()[1]
+        ));
+
+        let magic = loot.find(|item| {
+            item.get_prop("magic_power")
+                .and_then(|power| power.parse::<i32>().ok())
+                .is_some_and(|power| power > 10)
+        });
+        assert_eq!(
+            magic.iter().map(|item| &item.name).collect::<Vec<_>>(),
+            vec!["Orb", "Amulet"]
+        );
+
+        let powered = loot.find_by_prop("magic_power", "15");
+        assert_eq!(powered.len(), 2);
+        assert!(std::ptr::eq(powered[0], &loot.items()[1]));
+        assert!(loot.find_by_prop("magic_power", "99").is_empty());
+    }
+
+    #[test]
+    fn success_owned_items() {
+        fn load() -> Lootr<'static> {
+            let mut loot = Lootr::new();
+
+            for n in 0..10 {
+                let name = format!("Sword+{n}");
+                let props = HashMap::from([("bonus".to_string(), n.to_string())]);
+                loot.add(Item::owned(name, props));
+            }
+            loot.add_branch(
+                "shields",
+                Lootr::from(vec![Item::owned("Buckler".to_string(), HashMap::new())]),
+            );
+
+            loot
+        }
+
+        let loot = load();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        assert_eq!(loot.all_count(), 11);
+        assert_eq!(loot.branch("shields").unwrap().items()[0].props, None);
+
+        let item = loot.roll_uniform(ROOT, rng).unwrap();
+        assert!(item.name.starts_with("Sword+") || item.name == "Buckler");
+
+        let sword = loot.roll_seeded(ROOT, 0, 1.0, rng).unwrap();
+        assert_eq!(sword.get_prop("bonus"), Some(&sword.name[6..]));
+    }
+
+    #[test]
+    fn success_item_eq_and_hash() {
+        let mut props = Props::new();
+        props.insert("color", "red");
+        props.insert("size", "big");
+        props.insert("material", "wool");
+        let first = Item::from("Hat", props);
+
+        let mut props = Props::new();
+        props.insert("material", "wool");
+        props.insert("size", "big");
+        props.insert("color", "red");
+        let second = Item::from("Hat", props);
+
+        assert_eq!(first, second);
+        assert_ne!(first, Item::a("Hat"));
+
+        let set: HashSet<Item> = [first, second, Item::a("Hat")].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn success_dedup() {
+        let mut loot = stuffed();
+        loot.merge(stuffed());
+        loot.add(Item::a("Staff").with_weight(2));
+
+        assert_eq!(loot.all_count(), 19);
+
+        loot.dedup();
+
+        assert_eq!(loot.all_count(), 10);
+        assert_eq!(loot.self_count(), 2);
+        assert_eq!(loot.self_count_in("equipment/leather/Scraps"), Some(2));
+    }
+
+    #[test]
+    fn success_distinct_items() {
+        let mut loot = stuffed();
+        let potion = || Item::from("Potion", Props::from([("healing", "20")]));
+
+        loot.add_in(potion(), "weapons");
+        loot.add_in(potion(), "equipment/leather");
+        loot.add_in(
+            Item::from("Potion", Props::from([("healing", "40")])),
+            "weapons",
+        );
+
+        let distinct = loot.distinct_items();
+
+        assert_eq!(loot.all_count(), 12);
+        assert_eq!(distinct.len(), 11);
+        assert_eq!(distinct.iter().filter(|i| **i == potion()).count(), 1);
+    }
+
+    #[test]
+    fn success_roll_root() {
+        let loot = stuffed();
+
+        assert_eq!(
+            loot.roll(ROOT, 0, 1.0).unwrap().name,
+            "Staff",
+            "Should return the only element of root"
+        );
+    }
+
+    #[test]
+    fn success_roll_any() {
+        let loot = stuffed();
+        let picked = loot.roll_any().unwrap();
+
+        let expected = [
+            "Staff", "Bat", "Uzi", "Gloves", "Boots", "Jacket", "Pads", "ArmBand", "Patch",
+        ];
+        assert!(
+            expected.contains(&picked.name.as_ref()),
+            "Should return any element"
+        );
+    }
+
+    #[test]
+    fn success_roll_any_filtered() {
+        let mut loot = stuffed();
+        loot.add_in(
+            Item::from("Sword", Props::from([("attack", "10")])),
+            "weapons",
+        );
+        loot.add_in(
+            Item::from("Spikes", Props::from([("attack", "2")])),
+            "equipment/leather/Scraps",
+        );
+
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let picked: Vec<&Item> = (0..100)
+            .filter_map(|_| loot.roll_any_filtered(|item| item.has_prop("attack"), rng))
+            .collect();
+
+        assert!(!picked.is_empty(), "Should pick some items");
+        assert!(picked
+            .iter()
+            .all(|item| ["Sword", "Spikes"].contains(&item.name.as_ref())));
+
+        assert!(loot
+            .roll_any_filtered(|item| item.has_prop("defense"), rng)
+            .is_none());
+    }
+
+    #[test]
+    fn success_loot_target_rarity() {
+        let mut loot = Lootr::from(vec![
+            Item::a("Club").with_rarity(Rarity::Common),
+            Item::a("Stick").with_rarity(Rarity::Common),
+            Item::a("Stone").with_rarity(Rarity::Common),
+            Item::a("Crown").with_rarity(Rarity::Legendary),
+        ]);
+        loot.add_branch("plain", Lootr::from(vec![Item::a("Rock"), Item::a("Twig")]));
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let legendary = [DropBuilder::new().rarity(Rarity::Legendary).build()];
+        let plain = [DropBuilder::new().build()];
+
+        let rolls = 10_000;
+        let mut count = |drops: &[Drop]| {
+            let mut counts = HashMap::<Option<Rarity>, usize>::new();
+            (0..rolls).for_each(|_| {
+                for item in loot.loot_seeded(drops, rng) {
+                    *counts.entry(item.rarity).or_default() += 1;
+                }
+            });
+            counts
+        };
+
+        let targeted = count(&legendary);
+        let untargeted = count(&plain);
+
+        assert!(
+            targeted[&Some(Rarity::Legendary)]
+                > 10 * targeted.get(&Some(Rarity::Common)).unwrap_or(&1),
+            "Targeted drops should mostly yield legendary items, got {targeted:?}"
+        );
+        assert!(untargeted[&Some(Rarity::Common)] > untargeted[&Some(Rarity::Legendary)]);
+
+        let fallback = [DropBuilder::new()
+            .path("plain")
+            .rarity(Rarity::Legendary)
+            .build()];
+        let rewards = loot.loot_seeded(&fallback, rng);
+        assert!(["Rock", "Twig"].contains(&rewards[0].name.as_ref()));
+    }
+
+    #[test]
+    fn success_roll_any_by_rarity() {
+        let mut loot = Lootr::from(vec![Item::a("Club").with_rarity(Rarity::Common)]);
+        loot.add_branch(
+            "deep",
+            Lootr::from(vec![Item::a("Blade").with_rarity(Rarity::Rare)]),
+        );
+        loot.branch_mut("deep").unwrap().add_branch(
+            "deeper",
+            Lootr::from(vec![Item::a("Crown").with_rarity(Rarity::Legendary)]),
+        );
+        let weights = HashMap::from([
+            (Rarity::Common, 70.0),
+            (Rarity::Rare, 25.0),
+            (Rarity::Legendary, 5.0),
+        ]);
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let rolls = 100_000;
+        let mut counts = HashMap::<Rarity, usize>::new();
+        (0..rolls).for_each(|_| {
+            let item = loot.roll_any_by_rarity(&weights, rng).unwrap();
+            *counts.entry(item.rarity.unwrap()).or_default() += 1;
+        });
+
+        for (rarity, weight) in &weights {
+            let ratio = counts[rarity] as f64 / rolls as f64;
+            let expected = *weight as f64 / 100.0;
+            assert!(
+                (ratio - expected).abs() < 0.01,
+                "{rarity:?} should be {expected}, got {ratio}"
+            );
+        }
+
+        assert!(Lootr::new().roll_any_by_rarity(&weights, rng).is_none());
+    }
+
+    #[test]
+    fn success_item_tags() {
+        let mut sword = Item::from("Sword", Props::from([("attack", "3")]));
+
+        assert!(!sword.has_tag("two-handed"));
+        assert_eq!(format!("{sword}"), "Sword{attack=3}");
+
+        sword
+            .add_tag("two-handed")
+            .add_tag("cursed")
+            .add_tag("cursed");
+
+        assert!(sword.has_tag("two-handed"));
+        assert!(sword.has_tag("cursed"));
+        assert!(!sword.has_tag("starter"));
+        assert_eq!(sword.tags(), ["two-handed", "cursed"]);
+        assert_eq!(format!("{sword}"), "Sword{attack=3}[two-handed,cursed]");
+    }
+
+    #[test]
+    fn success_loot_require_tag() {
+        let mut loot = stuffed();
+        let mut ring = Item::a("Ring");
+        ring.add_tag("rare");
+        let mut amulet = Item::an("Amulet");
+        amulet.add_tag("rare").add_tag("magic");
+
+        loot.add_in(ring, "weapons")
+            .add_in(amulet, "equipment/leather");
+
+        let drops = [DropBuilder::new()
+            .anydepth()
+            .require_tag("rare")
+            .stack(2..=2)
+            .build()];
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let rewards: Vec<Item> = (0..50)
+            .flat_map(|_| loot.loot_seeded(&drops, rng))
+            .collect();
+
+        assert!(!rewards.is_empty());
+        rewards.iter().for_each(|item| {
+            assert!(
+                item.has_tag("rare"),
+                "Should only yield rare items, got {item}"
+            );
+        });
+
+        let none = [DropBuilder::new().require_tag("legendary").build()];
+        assert!(loot.loot_seeded(&none, rng).is_empty());
+    }
+
+    #[test]
+    fn success_roll_by_tags() {
+        let mut loot = stuffed();
+        let mut torch = Item::a("Torch");
+        torch.add_tag("fire");
+        let mut wand = Item::a("Wand");
+        wand.add_tag("ice").add_tag("magic");
+        let mut icicle = Item::a("Icicle");
+        icicle.add_tag("ice");
+
+        loot.add_in(torch, "weapons")
+            .add_in(wand, "equipment/leather")
+            .add(icicle);
+
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        (0..20).for_each(|_| {
+            let picked = loot.roll_by_tags(&["fire", "ice", "poison"], rng);
+
+            assert_eq!(picked.len(), 3);
+            assert_eq!(picked[0].unwrap().name, "Torch");
+            assert!(["Wand", "Icicle"].contains(&picked[1].unwrap().name.as_ref()));
+            assert!(picked[2].is_none(), "No item is tagged poison");
+        });
+    }
+
+    #[test]
+    fn success_roll_any_seeded() {
+        (1..9).for_each(|i| {
+            let loot = stuffed();
+            let first_picked = loot
+                .roll_seeded(
+                    ROOT,
+                    i16::MAX,
+                    1.0,
+                    &mut ChaCha20Rng::seed_from_u64(123 * i),
+                )
+                .unwrap();
+
+            (1..9).for_each(|_| {
+                let nloot = stuffed();
+                let picked = nloot
+                    .roll_seeded(
+                        ROOT,
+                        i16::MAX,
+                        1.0,
+                        &mut ChaCha20Rng::seed_from_u64(123 * i),
+                    )
+                    .unwrap();
+
+                assert_eq!(
+                    &picked.name, &first_picked.name,
+                    "Should return the same elements"
+                );
+            });
+        })
+    }
+
+    #[test]
+    fn success_threshold_dist() {
+        let mut loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        let rolls = 10_000;
+
+        let mut rate = |loot: &Lootr| {
+            let dropped = (0..rolls)
+                .filter(|_| loot.roll_seeded(ROOT, 0, 0.5, rng).is_some())
+                .count();
+            dropped as f64 / rolls as f64
+        };
+
+        let uniform = rate(&loot);
+        loot.set_threshold_dist(ThresholdDist::Beta(1.0, 3.0))
+            .unwrap();
+        let favored = rate(&loot);
+
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        loot.add_in(Item::a("Lance"), "weapons/polearms");
+        let late = (0..rolls)
+            .filter(|_| {
+                loot.roll_seeded(Some("weapons/polearms"), 0, 0.5, rng)
+                    .is_none()
+            })
+            .count();
+
+        assert!(
+            (uniform - 0.5).abs() < 0.03,
+            "Uniform draws should pass half the time, got {uniform}"
+        );
+        assert!(
+            favored > uniform + 0.3,
+            "Success favoring draws should drop more often, got {favored} and {uniform}"
+        );
+        assert!(
+            (late as f64 / rolls as f64) < 0.25,
+            "Branchs added afterwards should use the bag distribution"
+        );
+        assert_eq!(
+            loot.branch("weapons/polearms").unwrap().threshold_dist,
+            ThresholdDist::Uniform,
+            "Branchs keep their own setting, for rolls made on them"
+        );
+    }
+
+    #[test]
+    fn success_threshold_dist_extreme_params() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        let rolls = 2_000;
+
+        let samples = |dist: ThresholdDist, rng: &mut ChaCha20Rng| {
+            (0..rolls).map(|_| dist.sample(rng)).collect::<Vec<f32>>()
+        };
+
+        let large = samples(ThresholdDist::Beta(50.0, 50.0), rng);
+        let mean = large.iter().sum::<f32>() / rolls as f32;
+        assert!(
+            (mean - 0.5).abs() < 0.01,
+            "Should center on 0.5, got {mean}"
+        );
+        assert!(large.iter().all(|x| (0.3..=0.7).contains(x)));
+
+        let skewed = samples(ThresholdDist::Beta(1000.0, 1.0), rng);
+        assert!(skewed.iter().all(|x| *x > 0.98));
+
+        let tiny = samples(ThresholdDist::Beta(1e-12, 1e-12), rng);
+        assert!(tiny.iter().all(|x| *x == 0.0 || *x == 1.0));
+        assert!(tiny.contains(&0.0) && tiny.contains(&1.0));
+
+        let mut loot = loot;
+        loot.set_threshold_dist(ThresholdDist::Beta(50.0, 50.0))
+            .unwrap();
+        assert!((0..100).all(|_| loot.roll_seeded(ROOT, 0, 0.8, rng).is_some()));
+    }
+
+    #[test]
+    fn fail_threshold_dist_invalid_params() {
+        let mut loot = stuffed();
+
+        for dist in [
+            ThresholdDist::Beta(0.0, 1.0),
+            ThresholdDist::Beta(1.0, -2.0),
+            ThresholdDist::Beta(f32::NAN, 1.0),
+            ThresholdDist::Beta(1.0, f32::INFINITY),
+        ] {
+            assert_eq!(
+                loot.set_threshold_dist(dist).err(),
+                Some(LootrError::InvalidThresholdDist(format!("{dist:?}")))
+            );
+        }
+        assert_eq!(loot.threshold_dist, ThresholdDist::Uniform);
+    }
+
+    #[test]
+    fn success_roll_profiled() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let (_, profile) = loot.roll_profiled(ROOT, i16::MAX, 1.0, rng);
+        assert_eq!(profile.branches_visited, 5);
+        assert_eq!(profile.items_considered, 9);
+
+        let (_, profile) = loot.roll_profiled(Some("equipment"), 1, 1.0, rng);
+        assert_eq!(profile.branches_visited, 2);
+        assert_eq!(profile.items_considered, 4);
+
+        let (picked, profile) = loot.roll_profiled(ROOT, 0, 1e-9, rng);
+        assert!(picked.is_none());
+        assert_eq!(profile.rejections, 1);
+    }
+
+    #[test]
+    fn success_roll_zero_threshold() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let (picked, profile) = loot.roll_profiled(ROOT, i16::MAX, 0.0, rng);
+        assert!(picked.is_none());
+        assert_eq!(
+            profile,
+            RollProfile::default(),
+            "Should not traverse any branch"
+        );
+
+        assert!(loot.roll_seeded(ROOT, i16::MAX, -1.0, rng).is_none());
+
+        let drops = [
+            DropBuilder::new().anydepth().luck(0.0).build(),
+            DropBuilder::new().item("Uzi").luck(0.0).build(),
+        ];
+        assert!(loot.loot_seeded(&drops, rng).is_empty());
+    }
+
+    #[test]
+    fn success_candidates() {
+        let loot = stuffed();
+
+        let candidates = loot.candidates(ROOT, i16::MAX, 1.0, &mut ChaCha20Rng::seed_from_u64(123));
+        let names: Vec<&str> = candidates.iter().map(|item| item.name.as_ref()).collect();
+
+        assert_eq!(names, vec!["Staff", "Bat"]);
+
+        let picked = loot.roll_seeded(ROOT, i16::MAX, 1.0, &mut ChaCha20Rng::seed_from_u64(123));
+        assert!(names.contains(&picked.unwrap().name.as_ref()));
+    }
+
+    #[test]
+    fn success_roll_children_only() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let picked: Vec<&Item> = (0..200)
+            .filter_map(|_| loot.roll_children_only(ROOT, i16::MAX, 1.0, rng))
+            .collect();
+
+        assert!(!picked.is_empty(), "Should pick branch items");
+        assert!(picked.iter().all(|item| item.name != "Staff"));
+        assert!(loot.roll_children_only(ROOT, 0, 1.0, rng).is_none());
+    }
+
+    #[test]
+    fn success_fair_bag() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let rolls = 900;
+        let variance = |counts: HashMap<&str, usize>| {
+            let mean = rolls as f64 / loot.all_count() as f64;
+            let sum: f64 = counts.values().map(|c| (*c as f64 - mean).powi(2)).sum();
+            sum / loot.all_count() as f64
+        };
+
+        let mut fair = FairBag::new(&loot);
+        let mut fair_counts = HashMap::<&str, usize>::new();
+        (0..rolls).for_each(|_| {
+            let item = fair.next(rng).unwrap();
+            *fair_counts.entry(item.name.as_ref()).or_default() += 1;
+        });
+
+        let mut uniform_counts = HashMap::<&str, usize>::new();
+        (0..rolls).for_each(|_| {
+            let item = loot.roll_uniform(ROOT, rng).unwrap();
+            *uniform_counts.entry(item.name.as_ref()).or_default() += 1;
+        });
+
+        assert_eq!(fair_counts.len(), loot.all_count());
+
+        let fair_variance = variance(fair_counts);
+        let uniform_variance = variance(uniform_counts);
+        assert!(
+            fair_variance < uniform_variance / 4.0,
+            "Fair picks should be more even, got {fair_variance} and {uniform_variance}"
+        );
+
+        assert!(FairBag::new(&Lootr::new()).next(rng).is_none());
+    }
+
+    #[test]
+    fn success_roll_uniform() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let rolls = 100_000;
+        let mut counts = HashMap::<&str, usize>::new();
+        (0..rolls).for_each(|_| {
+            let item = loot.roll_uniform(ROOT, rng).unwrap();
+            *counts.entry(item.name.as_ref()).or_default() += 1;
+        });
+
+        assert_eq!(counts.len(), loot.all_count());
+
+        let staff = counts["Staff"] as f64 / rolls as f64;
+        let patch = counts["Patch"] as f64 / rolls as f64;
+        assert!(
+            (staff - patch).abs() < 0.01,
+            "Root and deepest items should be as likely, got {staff} and {patch}"
+        );
+
+        let weapon = loot.roll_uniform(Some("weapons"), rng).unwrap();
+        assert!(["Bat", "Uzi"].contains(&weapon.name.as_ref()));
+        assert!(Lootr::new().roll_uniform(ROOT, rng).is_none());
+    }
+
+    #[test]
+    fn success_roll_with_fallback() {
+        let mut loot = stuffed();
+        loot.branch_mut("weapons")
+            .unwrap()
+            .add_branch("legendary", Lootr::new());
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        assert!(loot
+            .roll_with_fallback(Some("weapons/legendary"), 0, 1.0, false, rng)
+            .is_none());
+
+        let picked = loot.roll_with_fallback(Some("weapons/legendary"), 0, 1.0, true, rng);
+        assert!(["Bat", "Uzi"].contains(&picked.unwrap().name.as_ref()));
+
+        let picked = loot.roll_with_fallback(Some("weapons"), 0, 1.0, true, rng);
+        assert!(["Bat", "Uzi"].contains(&picked.unwrap().name.as_ref()));
+    }
+
+    #[test]
+    fn success_roll_variant() {
+        let mut loot = stuffed();
+        loot.add_in(
+            Item::from("Sword", Props::from([("edge", "rusty")])).with_weight(3),
+            "weapons",
+        )
+        .add_in(
+            Item::from("Sword", Props::from([("edge", "sharp")])),
+            "weapons",
+        );
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let rusty = (0..4000)
+            .filter_map(|_| loot.roll_variant(Some("weapons"), "Sword", rng))
+            .filter(|item| item.get_prop("edge") == Some("rusty"))
+            .count();
+        let ratio = rusty as f64 / 4000.0;
+
+        assert!(
+            (ratio - 0.75).abs() < 0.03,
+            "Rusty swords should be 3/4 of the picks, got {ratio}"
+        );
+        assert!(loot.roll_variant(Some("weapons"), "Axe", rng).is_none());
+        assert!(loot.roll_variant(ROOT, "Sword", rng).is_none());
+    }
+
+    #[test]
+    fn success_roll_batch() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let picked: [_; 4] = loot.roll_batch(Some("weapons"), 0, 1.0, rng);
+
+        picked.iter().for_each(|item| {
+            assert!(
+                ["Bat", "Uzi"].contains(&item.unwrap().name.as_ref()),
+                "Should fill every slot with a weapon"
+            );
+        });
+    }
+
+    #[test]
+    fn success_roll_owned() {
+        let loot = stuffed();
+        let picked = loot.roll_owned(Some("weapons"), 0, 1.0).unwrap();
+        let seeded = loot
+            .roll_owned_seeded(ROOT, 0, 1.0, &mut ChaCha20Rng::seed_from_u64(123))
+            .unwrap();
+        drop(loot);
+
+        let name = std::thread::spawn(move || picked.name).join().unwrap();
+
+        assert!(["Bat", "Uzi"].contains(&name.as_ref()));
+        assert_eq!(seeded.name, "Staff");
+    }
+
+    #[test]
+    fn success_roll_in() {
+        let loot = stuffed();
+        let leather = loot.branch("equipment/leather").unwrap();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        (0..10).for_each(|_| {
+            let picked = Lootr::roll_in(leather, 0, 1.0, rng).unwrap();
+            assert!(["Jacket", "Pads"].contains(&picked.name.as_ref()));
+        });
+
+        let rolled = Lootr::roll_in(leather, 0, 1.0, &mut ChaCha20Rng::seed_from_u64(7));
+        let seeded = loot.roll_seeded(
+            Some("equipment/leather"),
+            0,
+            1.0,
+            &mut ChaCha20Rng::seed_from_u64(7),
+        );
+        assert_eq!(rolled, seeded, "Should match a roll on the same path");
+    }
+
+    #[test]
+    fn success_roll_until() {
+        let loot = Lootr::from(vec![
+            Item::a("Rock"),
+            Item::a("Stick"),
+            Item::from("Crown", Props::from([("rare", "yes")])),
+        ]);
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let (rolled, met) = loot.roll_until(ROOT, 0, 1.0, |i| i.has_prop("rare"), 1_000, rng);
+
+        assert!(met, "Should eventually roll a rare item");
+        assert!(rolled.last().unwrap().has_prop("rare"));
+        assert!(rolled[..rolled.len() - 1]
+            .iter()
+            .all(|i| !i.has_prop("rare")));
+
+        let (rolled, met) = loot.roll_until(ROOT, 0, 1.0, |i| i.has_prop("epic"), 5, rng);
+
+        assert!(!met, "Should give up after max attempts");
+        assert_eq!(rolled.len(), 5);
+    }
+
+    #[test]
+    fn success_roll_any_depth1() {
+        let loot = stuffed();
+        let picked = loot.roll(ROOT, 1, 1.0).unwrap();
+
+        let expected = ["Staff", "Bat", "Uzi", "Gloves", "Boots"];
+        assert!(
+            expected.contains(&picked.name.as_ref()),
+            "Should return a depth1 element"
+        );
+    }
+
+    #[test]
+    fn success_roll_any_depth1_branched() {
+        let loot = stuffed();
+        let picked = loot.roll(Some("/equipment/leather"), 0, 1.0).unwrap();
+
+        let expected = ["Jacket", "Pads"];
+        assert!(
+            expected.contains(&picked.name.as_ref()),
+            "Should return a depth1 element"
+        );
+    }
+
+    #[test]
+    fn success_roll_with_cooldown() {
+        let loot = Lootr::from(vec![Item::a("Chest")]);
+        let mut tracker = CooldownTracker::new(Duration::from_secs(60));
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        let now = Instant::now();
+
+        let first = loot.roll_with_cooldown(ROOT, &mut tracker, now, rng);
+        assert_eq!(first.unwrap().name, "Chest");
+
+        let later = now + Duration::from_secs(10);
+        let second = loot.roll_with_cooldown(ROOT, &mut tracker, later, rng);
+        assert!(second.is_none(), "Should still be in cooldown");
+
+        let much_later = now + Duration::from_secs(61);
+        let third = loot.roll_with_cooldown(ROOT, &mut tracker, much_later, rng);
+        assert_eq!(third.unwrap().name, "Chest", "Should be eligible again");
+    }
+
+    #[test]
+    fn success_roll_depth_weighted() {
+        let loot = stuffed();
+        let weights = [0.5, 0.3, 0.15, 0.05];
+        let depths = HashMap::from([
+            ("Staff", 0),
+            ("Bat", 1),
+            ("Uzi", 1),
+            ("Gloves", 1),
+            ("Boots", 1),
+            ("Jacket", 2),
+            ("Pads", 2),
+            ("ArmBand", 3),
+            ("Patch", 3),
+        ]);
+
+        let rolls = 20_000;
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        let mut per_depth = [0; 4];
+
+        (0..rolls).for_each(|_| {
+            let picked = loot.roll_depth_weighted(ROOT, &weights, rng).unwrap();
+            per_depth[depths[picked.name.as_ref()]] += 1;
+        });
+
+        per_depth
+            .iter()
+            .zip(weights.iter())
+            .for_each(|(count, weight)| {
+                let observed = f64::from(*count) / f64::from(rolls);
+                assert!(
+                    (observed - f64::from(*weight)).abs() < 0.02,
+                    "Depth should drop with a {weight} probability, got {observed}"
+                );
+            });
+
+        let picked = loot.roll_depth_weighted(Some("equipment"), &[0.0, 1.0], rng);
+        assert!(["Jacket", "Pads", "ArmBand", "Patch"].contains(&picked.unwrap().name.as_ref()));
+        assert!(loot.roll_depth_weighted(ROOT, &[], rng).is_none());
+    }
+
+    #[test]
+    fn success_roll_best_effort_rarity() {
+        let mut loot = stuffed();
+        loot.add_branch(
+            "junk",
+            Lootr::from(vec![
+                Item::a("Stick"),
+                Item::a("Rock").with_rarity(Rarity::Common),
+            ]),
+        );
+
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        (0..10).for_each(|_| {
+            let picked = loot
+                .roll_best_effort_rarity(Some("junk"), Rarity::Rare, rng)
+                .unwrap();
+            assert_eq!(picked.name, "Rock", "Should return the best common");
+        });
+
+        loot.add_in(Item::a("Gem").with_rarity(Rarity::Epic), "junk");
+
+        (0..10).for_each(|_| {
+            let picked = loot
+                .roll_best_effort_rarity(Some("junk"), Rarity::Rare, rng)
+                .unwrap();
+            assert_eq!(picked.name, "Gem", "Should return a rare or better");
+        });
+    }
+
+    #[test]
+    fn success_loot_any() {
+        let loot = stuffed();
+
+        let drops = [
+            Drop {
+                path: ROOT,
+                luck: 1.0,
+                depth: 1,
+                stack: 1..=1,
+                modify: false,
+                ..Default::default()
+            },
+            DropBuilder::new().path("equipment").luck(1.0).build(),
+            DropBuilder::new().path("weapons").luck(1.0).build(),
+        ];
+
+        let rewards = loot.loot(&drops);
+
+        assert!(rewards.len() >= 3, "Should reward at least 3 items");
+    }
+
+    #[test]
+    fn success_loot_weighted_stats() {
+        let loot = Lootr::from(vec![
+            Item::a("Dagger").with_weight(10),
+            Item::a("Sword"),
+            Item::a("Stick").with_weight(0),
+        ]);
+        let drops = [DropBuilder::new().luck(1.0).build()];
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let rewards: Vec<Cow<str>> = (0..11_000)
+            .flat_map(|_| loot.loot_seeded(&drops, rng))
+            .map(|r| r.name)
+            .collect();
+
+        let daggers = rewards.iter().filter(|name| **name == "Dagger").count();
+        let swords = rewards.iter().filter(|name| **name == "Sword").count();
+        let ratio = daggers as f64 / swords as f64;
+
+        assert_eq!(daggers + swords, rewards.len(), "Should never pick Stick");
+        assert!(
+            (ratio - 10.0).abs() < 1.0,
+            "Dagger should drop about 10x more than Sword, got {ratio}"
+        );
+
+        let replayed: Vec<Cow<str>> = (0..100)
+            .flat_map(|_| loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(7)))
+            .map(|r| r.name)
+            .collect();
+        assert!(replayed.iter().all(|name| *name == replayed[0]));
+    }
+
+    #[test]
+    fn success_loot_stats() {
+        let loot = stuffed();
+
+        let luck_for_equipment = 0.3;
+        let luck_for_weapons = 0.8;
+        let drops = [
+            DropBuilder::new()
+                .path("equipment")
+                .luck(luck_for_equipment)
+                .anydepth()
+                .build(),
+            DropBuilder::new()
+                .path("weapons")
+                .luck(luck_for_weapons)
+                .anydepth()
+                .build(),
+        ];
+
+        let rolls = 100_000;
+        let f_rolls: f64 = Into::<f64>::into(rolls);
+        let mut overall_count = 0;
+        let mut overall_rewards = HashMap::<Cow<str>, i32>::new();
+
+        (0..rolls).for_each(|_| {
+            loot.loot(&drops).iter().for_each(|r| {
+                let current = match overall_rewards.get(r.name.as_ref()) {
+                    Some(number) => *number,
+                    None => 0,
+                };
+                overall_rewards.insert(r.name.clone(), current + 1);
+                overall_count += 1;
+            })
+        });
+
+        let gloves = overall_rewards.get("Gloves");
+        let boots = overall_rewards.get("Boots");
+        let jacket = overall_rewards.get("Jacket");
+        let pads = overall_rewards.get("Pads");
+        let armband = overall_rewards.get("ArmBand");
+        let patch = overall_rewards.get("Patch");
+        let bat = overall_rewards.get("Bat");
+        let uzi = overall_rewards.get("Uzi");
+
+        assert_ne!(gloves, None, "There should be some Gloves");
+        assert_ne!(boots, None, "There should be some Boots");
+        assert_ne!(jacket, None, "There should be some Jacket");
+        assert_ne!(pads, None, "There should be some Pads");
+        assert_ne!(armband, None, "There should be some ArmBand");
+        assert_ne!(patch, None, "There should be some Patch");
+        assert_ne!(bat, None, "There should be some Bat");
+        assert_ne!(uzi, None, "There should be some Uzi");
+
+        let zero = &0;
+        let equipment = overall_rewards.get("Gloves").unwrap_or(zero)
+            + overall_rewards.get("Boots").unwrap_or(zero)
+            + overall_rewards.get("Jacket").unwrap_or(zero)
+            + overall_rewards.get("Pads").unwrap_or(zero)
+            + overall_rewards.get("ArmBand").unwrap_or(zero)
+            + overall_rewards.get("Patch").unwrap_or(zero);
+
+        let weapons =
+            overall_rewards.get("Bat").unwrap_or(zero) + overall_rewards.get("Uzi").unwrap_or(zero);
+
+        assert_eq!(equipment + weapons, overall_count);
+
+        let theory = f_rolls * Into::<f64>::into(luck_for_equipment);
+        let expected_equipment = (theory * 0.7)..(theory * 1.6);
+
+        let theory = f_rolls * Into::<f64>::into(luck_for_weapons);
+        let expected_weapons = (theory * 0.7)..(theory * 1.6);
+
+        assert!(
+            expected_equipment.contains(&equipment.into()),
+            "There should be enough equipment"
+        );
+        assert!(
+            expected_weapons.contains(&weapons.into()),
+            "There should be enough weapons"
+        );
+    }
+
+    #[test]
+    fn success_loot_stack_dist() {
+        let loot = Lootr::from(vec![Item::a("Coin")]);
+        let drops = [Drop {
+            stack_dist: Some(vec![(1, 0.8), (2, 0.15), (5, 0.05)]),
+            ..DropBuilder::new().luck(1.0).build()
+        }];
+
+        let rolls = 20_000;
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        let mut sizes = HashMap::<usize, i32>::new();
+
+        (0..rolls).for_each(|_| {
+            *sizes
+                .entry(loot.loot_seeded(&drops, rng).len())
+                .or_default() += 1;
+        });
+
+        assert_eq!(sizes.keys().filter(|k| ![1, 2, 5].contains(*k)).count(), 0);
+
+        [(1, 0.8), (2, 0.15), (5, 0.05)]
+            .iter()
+            .for_each(|(size, weight)| {
+                let observed = f64::from(*sizes.get(size).unwrap_or(&0)) / f64::from(rolls);
+                assert!(
+                    (observed - weight).abs() < 0.02,
+                    "Stack {size} should drop with a {weight} probability, got {observed}"
+                );
+            });
+    }
+
+    #[test]
+    fn success_drop_parse() {
+        let drop = Drop::parse("weapons@3 luck=0.5 x1-3").unwrap();
+        assert_eq!(drop.path, Some("weapons"));
+        assert_eq!(drop.depth, 3);
+        assert_eq!(drop.luck, 0.5);
+        assert_eq!(drop.stack, 1..=3);
+
+        let drop = Drop::parse("x2 equipment/leather@*").unwrap();
+        assert_eq!(drop.path, Some("equipment/leather"));
+        assert_eq!(drop.depth, i16::MAX);
+        assert_eq!(drop.stack, 2..=2);
+
+        let drop = Drop::parse("").unwrap();
+        assert_eq!(drop.path, ROOT);
+        assert_eq!(drop.depth, 1);
+        assert_eq!(drop.luck, 1.0);
+
+        assert_eq!(Drop::parse("@2").unwrap().depth, 2);
+    }
+
+    #[test]
+    fn success_drop_parse_runtime_spec() {
+        let loot = stuffed();
+        let specs = String::from("equipment/leather@0 x2\nweapons@0");
+
+        let drops: Vec<Drop> = specs
+            .lines()
+            .map(|spec| Drop::parse(spec).unwrap())
+            .collect();
+        assert_eq!(drops[0].path, Some("equipment/leather"));
+
+        let (rewards, log) = loot.loot_logged(&drops, 123);
+        assert_eq!(rewards.len(), 3);
+        assert_eq!(log.entries[0].path, "equipment/leather");
+        assert_eq!(log.entries[2].path, "weapons");
+    }
+
+    #[test]
+    fn success_drop_runtime_names() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        let (name, key) = (String::from("Jacket"), String::from("key"));
+
+        let drops = [Drop {
+            path: Some("equipment"),
+            depth: i16::MAX,
+            item: Some(name.as_str()),
+            requires: vec![key.as_str()],
+            ..Drop::default()
+        }];
+        let rewards = loot.loot_with_inventory(&drops, &HashSet::from(["key"]), rng);
+        assert_eq!(rewards.len(), 1);
+        assert_eq!(rewards[0].name, "Jacket");
+
+        let sets = [DropSet {
+            items: vec![name.as_str()],
+            path: Some("equipment/leather"),
+            ..DropSet::default()
+        }];
+        assert_eq!(loot.loot_sets(&sets, rng).len(), 1);
+    }
+
+    #[test]
+    fn success_drop_parse_errors() {
+        assert_eq!(
+            Drop::parse("weapons@deep").err(),
+            Some(ParseError::InvalidDepth(String::from("weapons@deep")))
+        );
+        assert_eq!(
+            Drop::parse("weapons luck=lots").err(),
+            Some(ParseError::InvalidLuck(String::from("luck=lots")))
+        );
+        assert_eq!(
+            Drop::parse("weapons luck=-1").err(),
+            Some(ParseError::InvalidLuck(String::from("luck=-1")))
+        );
+        assert_eq!(
+            Drop::parse("weapons x3-1").err(),
+            Some(ParseError::InvalidStack(String::from("x3-1")))
+        );
+        assert_eq!(
+            Drop::parse("weapons x1-").err(),
+            Some(ParseError::InvalidStack(String::from("x1-")))
+        );
+        assert_eq!(
+            Drop::parse("weapons armors").err(),
+            Some(ParseError::UnexpectedToken(String::from("armors")))
+        );
+        assert_eq!(
+            Drop::parse("weapons color=red").err(),
+            Some(ParseError::UnexpectedToken(String::from("color=red")))
+        );
+    }
+
+    #[test]
+    fn success_drop_builder_modify() {
+        assert!(DropBuilder::new().modify(true).build().modify);
+        assert!(!DropBuilder::new().modified().modify(false).build().modify);
+
+        let drop = DropBuilder::new()
+            .modify(true)
+            .path("weapons")
+            .luck(0.5)
+            .build();
+        assert!(drop.modify);
+        assert_eq!(drop.path, Some("weapons"));
+        assert_eq!(drop.luck, 0.5);
+
+        let drop = DropBuilder::new()
+            .path("weapons")
+            .luck(0.5)
+            .modified()
+            .build();
+        assert!(drop.modify);
+        assert_eq!(drop.path, Some("weapons"));
+        assert_eq!(drop.luck, 0.5);
+    }
+
+    #[test]
+    fn success_drop_builder_stack_dist() {
+        let drop = DropBuilder::new()
+            .path("weapons")
+            .stack(1..=3)
+            .stack_dist(vec![(1, 0.8), (2, 0.15), (5, 0.05)])
+            .unwrap()
+            .build();
+
+        assert_eq!(drop.stack_dist, Some(vec![(1, 0.8), (2, 0.15), (5, 0.05)]));
+        assert_eq!(drop.stack, 1..=3);
+
+        let drop = DropBuilder::new()
+            .stack_dist(vec![(1, 3.0), (2, 1.0)])
+            .unwrap()
+            .build();
+        assert_eq!(drop.stack_dist, Some(vec![(1, 0.75), (2, 0.25)]));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "cannot use path \"weapons/missing\", this branch does not exist: missing"
+    )]
+    fn fail_roll_in_missing_branch() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        loot.roll_seeded(Some("weapons/missing"), 0, 1.0, rng);
+    }
+
+    #[test]
+    fn fail_drop_builder_invalid_stack_dist() {
+        assert_eq!(
+            DropBuilder::new()
+                .stack_dist(vec![(1, 1.0), (2, -0.5)])
+                .err(),
+            Some(LootrError::InvalidStackDist(String::from("(2, -0.5)")))
+        );
+        assert!(DropBuilder::new().stack_dist(vec![(1, f32::NAN)]).is_err());
+    }
+
+    #[test]
+    fn success_loot_names() {
+        let mut loot = stuffed();
+        fn renamed(source: Item) -> Item {
+            source.extend("Renamed", Props::new())
+        }
+        loot.branch_mut("weapons").unwrap().add_modifier(renamed);
+
+        let drops = [
+            DropBuilder::new()
+                .path("equipment")
+                .anydepth()
+                .stack(1..=3)
+                .build(),
+            DropBuilder::new()
+                .path("equipment")
+                .luck(0.001)
+                .guaranteed(true)
+                .build(),
+            DropBuilder::new()
+                .path("weapons")
+                .luck(1.0)
+                .modified()
+                .build(),
+        ];
+
+        let names = loot.loot_names(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+        let rewards = loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+
+        assert!(names.len() >= 3, "Should always yield the guaranteed drop");
+        assert_eq!(names.len(), rewards.len());
+        assert_eq!(
+            names,
+            rewards
+                .iter()
+                .map(|item| item.name.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(names.last().map(|name| name.as_ref()), Some("Renamed"));
+    }
+
+    #[test]
+    fn success_unique_drops_in_every_loot() {
+        let loot = stuffed();
+        let inventory = HashSet::new();
+        let drop = DropBuilder::new()
+            .path("weapons")
+            .depth(0)
+            .luck(1.0)
+            .stack(2..=2)
+            .unique(true)
+            .build();
+        let drops = [drop.clone(), drop.clone(), drop];
+
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        let looted: Vec<Vec<Item>> = vec![
+            loot.loot_seeded_with_meta(&drops, 123).0,
+            loot.loot_with_luck_fn(&drops, |d| d.luck, rng),
+            loot.loot_with_inventory(&drops, &inventory, rng),
+            loot.loot_normalized(&drops, 3, rng),
+            loot.loot_with_upgrade(&drops, 0.0, rng),
+            loot.loot_distinct_branches(&drops, rng),
+        ];
+
+        for rewards in looted {
+            let mut names: Vec<&str> = rewards.iter().map(|item| item.name.as_ref()).collect();
+            names.sort();
+            assert_eq!(names, vec!["Bat", "Uzi"]);
+        }
+
+        let mut names = loot.loot_names(&drops, rng);
+        names.sort();
+        assert_eq!(names, vec!["Bat", "Uzi"]);
+
+        let report = loot.drop_report(&drops, 100, rng);
+        assert_eq!(report.expected_items, 2.0);
+    }
+
+    #[test]
+    fn success_loot_with_luck_fn() {
+        let loot = stuffed();
+        let drops = [DropBuilder::new().path("weapons").luck(0.1).build()];
+
+        let count = |progress: f32| {
+            let rng = &mut ChaCha20Rng::seed_from_u64(123);
+            (0..1000)
+                .map(|_| {
+                    loot.loot_with_luck_fn(&drops, |d| d.luck + progress, rng)
+                        .len()
+                })
+                .sum::<usize>()
+        };
+
+        assert!(
+            count(0.8) > count(0.0),
+            "Higher luck should yield more items"
+        );
+    }
+
+    #[test]
+    fn success_loot_with_upgrade() {
+        let mut loot = stuffed();
+        loot.add_branch(
+            "blades",
+            Lootr::from(vec![
+                Item::a("Dagger").with_rarity(Rarity::Common),
+                Item::a("Blade").with_rarity(Rarity::Uncommon),
+                Item::a("Sword").with_rarity(Rarity::Rare),
+            ]),
+        );
+        let drops = [DropBuilder::new()
+            .path("blades")
+            .item("Dagger")
+            .luck(1.0)
+            .build()];
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let rolls = 10_000;
+        let rewards: Vec<Item> = (0..rolls)
+            .flat_map(|_| loot.loot_with_upgrade(&drops, 0.25, rng))
+            .collect();
+
+        assert_eq!(rewards.len(), rolls);
+        assert!(
+            rewards.iter().all(|r| r.name != "Sword"),
+            "Should only upgrade to the next tier"
+        );
+
+        let upgraded = rewards.iter().filter(|r| r.name == "Blade").count();
+        let ratio = upgraded as f64 / rolls as f64;
+
+        assert!(
+            (ratio - 0.25).abs() < 0.02,
+            "Should upgrade about 1/4 of the items, got {ratio}"
+        );
+
+        let never = loot.loot_with_upgrade(&drops, 0.0, rng);
+        assert_eq!(never[0].name, "Dagger");
+    }
+
+    #[test]
+    fn success_loot_normalized() {
+        let loot = stuffed();
+        let drops = [
+            DropBuilder::new().path("weapons").luck(3.0).build(),
+            DropBuilder::new().path("equipment").luck(1.0).build(),
+        ];
+
+        let picks = 10_000;
+        let rewards = loot.loot_normalized(&drops, picks, &mut ChaCha20Rng::seed_from_u64(123));
+
+        assert_eq!(rewards.len(), picks, "Should yield one item per pick");
+
+        let weapons = rewards
+            .iter()
+            .filter(|r| ["Bat", "Uzi"].contains(&r.name.as_ref()))
+            .count();
+        let ratio = weapons as f64 / picks as f64;
+
+        assert!(
+            (ratio - 0.75).abs() < 0.03,
+            "Weapons should be 3/4 of the picks, got {ratio}"
+        );
+    }
+
+    #[test]
+    fn success_loot_forced_item() {
+        let loot = stuffed();
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let drops = [DropBuilder::new()
+            .path("weapons")
+            .item("Uzi")
+            .luck(1.0)
+            .stack(2..=2)
+            .build()];
+
+        (0..10).for_each(|_| {
+            let rewards = loot.loot_seeded(&drops, rng);
+            assert_eq!(rewards.len(), 2);
+            assert!(rewards.iter().all(|r| r.name == "Uzi"));
+        });
+
+        let drops = [DropBuilder::new()
+            .path("weapons")
+            .item("Gloves")
+            .luck(1.0)
+            .build()];
+        assert!(
+            loot.loot_seeded(&drops, rng).is_empty(),
+            "Should not find Gloves"
+        );
+    }
+
+    #[test]
+    fn success_loot_seeded() {
+        let loot = stuffed();
+        let drops = [
+            DropBuilder::new().path("equipment").anydepth().build(),
+            DropBuilder::new().path("weapons").anydepth().build(),
+        ];
+
+        let rewards = loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+
+        (0..10).for_each(|_| {
+            let nloot = stuffed();
+            let nrewards = nloot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+
+            nrewards.iter().enumerate().for_each(|(i, r)| {
+                assert_eq!(
+                    r.name,
+                    rewards.get(i).unwrap().name,
+                    "Should return same elements"
+                )
+            });
+        });
+    }
+
+    #[test]
+    fn success_default_props() {
+        let mut loot = stuffed();
+        loot.ensure_branch("fire")
+            .set_default_prop("element", "fire")
+            .set_default_prop("damage", "3")
+            .add(Item::a("Torch"))
+            .add(Item::from("Wand", Props::from([("damage", "5")])));
+        loot.set_default_prop("origin", "chest");
+
+        let drops = [DropBuilder::new().path("fire").stack(10..=10).build()];
+        let rewards = loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+
+        assert_eq!(rewards.len(), 10);
+        rewards.iter().for_each(|item| {
+            assert_eq!(item.get_prop("element"), Some("fire"));
+            assert_eq!(item.get_prop("origin"), Some("chest"));
+            match item.name.as_ref() {
+                "Wand" => assert_eq!(item.get_prop("damage"), Some("5")),
+                _ => assert_eq!(item.get_prop("damage"), Some("3")),
+            }
+        });
+
+        let weapons = [DropBuilder::new().path("weapons").build()];
+        let rewards = loot.loot_seeded(&weapons, &mut ChaCha20Rng::seed_from_u64(123));
+        assert_eq!(rewards[0].get_prop("element"), None);
+        assert_eq!(loot.branch("fire").unwrap().items()[0].props, None);
+    }
+
+    #[test]
+    fn success_loot_capped_seeded() {
+        let loot = stuffed();
+        let drops = [
+            DropBuilder::new().path("weapons").stack(3..=3).build(),
+            DropBuilder::new().path("equipment").stack(4..=4).build(),
+            DropBuilder::new().anydepth().stack(5..=5).build(),
+        ];
+
+        let rewards = loot.loot_capped_seeded(&drops, 5, &mut ChaCha20Rng::seed_from_u64(123));
+        assert_eq!(rewards.len(), 5);
+        assert!(["Bat", "Uzi"].contains(&rewards[0].name.as_ref()));
+        assert!(["Bat", "Uzi"].contains(&rewards[2].name.as_ref()));
+        assert!(!["Bat", "Uzi"].contains(&rewards[3].name.as_ref()));
+
+        let none = loot.loot_capped_seeded(&drops, 0, &mut ChaCha20Rng::seed_from_u64(123));
+        assert!(none.is_empty());
+
+        let all = loot.loot_capped_seeded(&drops, 100, &mut ChaCha20Rng::seed_from_u64(123));
+        let plain = loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+        assert_eq!(all, plain);
+    }
+
+    #[test]
+    fn success_loot_count_range() {
+        let loot = stuffed();
+        let drops = [
+            DropBuilder::new().path("weapons").luck(0.3).build(),
+            DropBuilder::new()
+                .path("equipment")
+                .anydepth()
+                .stack(1..=3)
+                .build(),
+        ];
+
+        (0..200).for_each(|seed| {
+            let rewards =
+                loot.loot_count_range(&drops, 2, 4, &mut ChaCha20Rng::seed_from_u64(seed));
+
+            assert!(
+                (2..=4).contains(&rewards.len()),
+                "Should loot between 2 and 4 items, got {}",
+                rewards.len()
+            );
+        });
+
+        let never = [DropBuilder::new().luck(0.0).build()];
+        let rewards = loot.loot_count_range(&never, 2, 4, &mut ChaCha20Rng::seed_from_u64(1));
+        assert!(
+            rewards.is_empty(),
+            "Should give up on tables yielding nothing"
+        );
+    }
+
+    #[test]
+    fn success_loot_distinct_branches() {
+        let loot = stuffed();
+        let drop = DropBuilder::new().path("equipment").anydepth().build();
+        let drops = [drop.clone(), drop.clone(), drop];
+
+        let branch_of = |name: &str| match name {
+            "Gloves" | "Boots" => "equipment",
+            "Jacket" | "Pads" => "leather",
+            _ => "Scraps",
+        };
+
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        let mut looted = 0;
+
+        (0..200).for_each(|_| {
+            let rewards = loot.loot_distinct_branches(&drops, rng);
+            let branches: HashSet<&str> = rewards.iter().map(|r| branch_of(&r.name)).collect();
+
+            assert_eq!(
+                branches.len(),
+                rewards.len(),
+                "Should hit distinct branches"
+            );
+            looted += rewards.len();
+        });
+
+        assert!(looted > 200);
+
+        let drop = DropBuilder::new().path("weapons").luck(1.0).build();
+        let drops = [drop.clone(), drop];
+        let rewards = loot.loot_distinct_branches(&drops, rng);
+        assert_eq!(rewards.len(), 2, "Should fall back to used branches");
+
+        let drop = DropBuilder::new()
+            .path("equipment")
+            .depth(0)
+            .luck(1.0)
+            .build();
+        let drops = [drop.clone(), drop];
+        let rewards = loot.loot_distinct_branches(&drops, rng);
+        assert_eq!(
+            rewards.len(),
+            2,
+            "Should ignore fresh branches out of the drop depth"
+        );
+    }
+
+    #[test]
+    fn success_loot_filtered() {
+        let mut loot = Lootr::new();
+        loot.add_branch(
+            "armory",
+            Lootr::from(vec![
+                Item::from("Sword", Props::from([("attack", "10")])),
+                Item::from("Shield", Props::from([("defense", "8")])),
+                Item::from("Helmet", Props::from([("defense", "3")])),
+            ]),
+        );
+        let drops = [DropBuilder::new()
+            .path("armory")
+            .luck(1.0)
+            .filter(|item| item.has_prop("attack"))
+            .build()];
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let rewards: Vec<Item> = (0..300)
+            .flat_map(|_| loot.loot_seeded(&drops, rng))
+            .collect();
+
+        assert!(!rewards.is_empty());
+        assert!(rewards.iter().all(|item| item.name == "Sword"));
+        assert!(
+            rewards.len() < 200,
+            "Should yield nothing when the pick is rejected"
+        );
+    }
+
+    #[test]
+    fn success_loot_guaranteed() {
+        let mut loot = stuffed();
+        loot.add_branch("empty", Lootr::new());
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let drops = [DropBuilder::new()
+            .path("weapons")
+            .luck(0.01)
+            .guaranteed(true)
+            .build()];
+
+        (0..100).for_each(|_| {
+            let rewards = loot.loot_seeded(&drops, rng);
+            assert_eq!(rewards.len(), 1, "Should always yield a weapon");
+        });
+
+        let drops = [DropBuilder::new()
+            .path("empty")
+            .luck(0.01)
+            .guaranteed(true)
+            .build()];
+        assert!(loot.loot_seeded(&drops, rng).is_empty());
+    }
+
+    #[test]
+    fn success_loot_unique() {
+        let loot = stuffed();
+        let drop = DropBuilder::new()
+            .path("weapons")
+            .luck(1.0)
+            .stack(5..=5)
+            .unique(true)
+            .build();
+        let drops = [drop.clone(), drop.clone(), drop];
+
+        (0..100).for_each(|seed| {
+            let rewards = loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(seed));
+            let names: HashSet<&str> = rewards.iter().map(|item| item.name.as_ref()).collect();
+
+            assert_eq!(rewards.len(), 2, "Should loot each weapon once");
+            assert_eq!(names.len(), rewards.len());
+        });
 
-            (1..9).for_each(|_| {
-                let nloot = stuffed();
-                let picked = nloot
-                    .roll_seeded(
-                        ROOT,
-                        i16::MAX,
-                        1.0,
-                        &mut ChaCha20Rng::seed_from_u64(123 * i),
-                    )
-                    .unwrap();
+        let drops = [
+            DropBuilder::new().path("weapons").luck(1.0).build(),
+            DropBuilder::new().path("weapons").luck(1.0).build(),
+            DropBuilder::new().anydepth().luck(1.0).stack(3..=3).build(),
+        ];
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
 
-                assert_eq!(
-                    &picked.name, &first_picked.name,
-                    "Should return the same elements"
+        (0..100).for_each(|_| {
+            let rewards = loot.loot_unique_seeded(&drops, rng);
+            let names: HashSet<&str> = rewards.iter().map(|item| item.name.as_ref()).collect();
+
+            assert!(rewards.len() <= drops.len());
+            assert_eq!(names.len(), rewards.len(), "Should not repeat names");
+        });
+    }
+
+    #[test]
+    fn success_new_seeded() {
+        fn seeded(seed: u64) -> Lootr<'static> {
+            let mut loot = Lootr::new_seeded(seed);
+            loot.add(Item::a("Staff"))
+                .add_branch("weapons", Lootr::from(vec![Item::a("Bat"), Item::a("Uzi")]))
+                .add_branch(
+                    "equipment",
+                    Lootr::from(vec![Item::a("Gloves"), Item::a("Boots")]),
                 );
-            });
-        })
+            loot
+        }
+
+        let drops = [DropBuilder::new().anydepth().luck(1.0).stack(1..=3).build()];
+        let sequence = |loot: &mut Lootr<'static>| -> Vec<Vec<Cow<'static, str>>> {
+            (0..5)
+                .map(|_| {
+                    loot.loot_next(&drops)
+                        .into_iter()
+                        .map(|item| item.name)
+                        .collect()
+                })
+                .collect()
+        };
+
+        let mut first = seeded(123);
+        let mut second = seeded(123);
+
+        assert_eq!(sequence(&mut first), sequence(&mut second));
+        assert_eq!(
+            (0..5)
+                .map(|_| first.roll_any_next().map(|i| i.name))
+                .collect::<Vec<_>>(),
+            (0..5)
+                .map(|_| second.roll_any_next().map(|i| i.name))
+                .collect::<Vec<_>>()
+        );
     }
 
     #[test]
-    fn success_roll_any_depth1() {
-        let loot = stuffed();
-        let picked = loot.roll(ROOT, 1, 1.0).unwrap();
+    fn success_new_seeded_entropy_roll() {
+        let mut loot = Lootr::new_seeded(123);
+        loot.add(Item::a("Staff")).add(Item::a("Bat"));
+        let mut seeded = Lootr::new_seeded(123);
+        seeded.add(Item::a("Staff")).add(Item::a("Bat"));
+
+        for _ in 0..10 {
+            assert!(loot.roll_any().is_some());
+            assert!(loot.loot(&[Drop::default()]).len() <= 1);
+        }
+        assert_eq!(
+            (0..10)
+                .map(|_| loot.roll_any_next().map(|i| i.name))
+                .collect::<Vec<_>>(),
+            (0..10)
+                .map(|_| seeded.roll_any_next().map(|i| i.name))
+                .collect::<Vec<_>>(),
+            "Should not advance the held PRNG from shared rolls"
+        );
+    }
 
-        let expected = ["Staff", "Bat", "Uzi", "Gloves", "Boots"];
+    #[test]
+    fn success_replace_contents_keeps_settings() {
+        let drops = [DropBuilder::new().luck(1.0).stack(1..=3).build()];
+        let reloaded = || {
+            let mut bag = Lootr::from(vec![Item::a("Shield"), Item::a("Helmet")]);
+            bag.set_default_prop("origin", "vault");
+            bag
+        };
+
+        let mut loot = Lootr::new_seeded(123);
+        loot.set_default_prop("origin", "chest");
+        loot.replace_contents(reloaded());
+
+        let mut seeded = Lootr::new_seeded(123);
+        seeded.replace_contents(reloaded());
+
+        let rewards = loot.loot_next(&drops);
         assert_eq!(
-            expected.contains(&picked.name),
-            true,
-            "Should return a depth1 element"
+            rewards[0].get_prop("origin"),
+            Some("vault"),
+            "Should take the new default props"
+        );
+
+        loot.replace_contents(Lootr::from(vec![Item::a("Shield")]));
+        assert_eq!(loot.loot_next(&drops)[0].get_prop("origin"), None);
+        assert_eq!(
+            rewards.iter().map(|item| &item.name).collect::<Vec<_>>(),
+            seeded
+                .loot_next(&drops)
+                .iter()
+                .map(|item| &item.name)
+                .collect::<Vec<_>>(),
+            "Should keep the seeded PRNG"
         );
     }
 
     #[test]
-    fn success_roll_any_depth1_branched() {
+    fn success_loot_seeded_golden() {
         let loot = stuffed();
-        let picked = loot.roll(Some("/equipment/leather"), 0, 1.0).unwrap();
+        let drops = [
+            DropBuilder::new()
+                .path("equipment")
+                .anydepth()
+                .stack(1..=3)
+                .build(),
+            DropBuilder::new().path("weapons").luck(1.0).build(),
+            DropBuilder::new().anydepth().luck(0.5).build(),
+        ];
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let rewards: Vec<Vec<Cow<str>>> = (0..4)
+            .map(|_| {
+                loot.loot_seeded(&drops, rng)
+                    .into_iter()
+                    .map(|item| item.name)
+                    .collect()
+            })
+            .collect();
 
-        let expected = ["Jacket", "Pads"];
+        // seeded results must not change, whatever the rand version
         assert_eq!(
-            expected.contains(&picked.name),
-            true,
-            "Should return a depth1 element"
+            rewards,
+            vec![
+                vec!["Boots", "Boots", "Bat", "Bat"],
+                vec!["Jacket", "Bat", "Bat"],
+                vec!["ArmBand", "ArmBand", "ArmBand", "Uzi", "Gloves"],
+                vec!["Boots", "Boots", "Uzi", "Staff"],
+            ]
         );
     }
 
     #[test]
-    fn success_loot_any() {
+    fn success_loot_seeded_with_meta() {
         let loot = stuffed();
-
         let drops = [
-            Drop {
-                path: ROOT,
-                luck: 1.0,
-                depth: 1,
-                stack: 1..=1,
-                modify: false,
-            },
-            DropBuilder::new().path("equipment").luck(1.0).build(),
-            DropBuilder::new().path("weapons").luck(1.0).build(),
+            DropBuilder::new()
+                .path("weapons")
+                .luck(1.0)
+                .stack(1..=3)
+                .build(),
+            DropBuilder::new().path("equipment").anydepth().build(),
         ];
 
-        let rewards = loot.loot(&drops);
+        let (rewards, meta) = loot.loot_seeded_with_meta(&drops, 42);
+
+        assert_eq!(meta.seed, 42);
+        assert_eq!(meta.drop_indices.len(), rewards.len());
+        assert_eq!(meta.drop_indices.first(), Some(&0));
+
+        let (replayed, replayed_meta) = loot.loot_seeded_with_meta(&drops, meta.seed);
 
-        assert_eq!(rewards.len() >= 3, true, "Should reward at least 3 items");
+        assert_eq!(replayed, rewards, "Should replay the same items");
+        assert_eq!(replayed_meta, meta);
     }
 
     #[test]
-    fn success_loot_stats() {
+    fn success_loot_with_paths_seeded() {
         let loot = stuffed();
-
-        let luck_for_equipment = 0.3;
-        let luck_for_weapons = 0.8;
         let drops = [
+            DropBuilder::new()
+                .path("weapons")
+                .luck(1.0)
+                .stack(3..=3)
+                .build(),
             DropBuilder::new()
                 .path("equipment")
-                .luck(luck_for_equipment)
                 .anydepth()
+                .guaranteed(true)
                 .build(),
+        ];
+
+        let rewards = loot.loot_with_paths_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(42));
+
+        assert_eq!(rewards.len(), 4);
+        assert!(rewards[..3].iter().all(|(path, _)| path == "weapons"));
+        assert!(rewards[3].0.starts_with("equipment"));
+        assert!(loot
+            .branch(&rewards[3].0)
+            .unwrap()
+            .items()
+            .iter()
+            .any(|item| item.name == rewards[3].1.name));
+
+        let plain = loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(42));
+        let items: Vec<Item> = rewards.into_iter().map(|(_, item)| item).collect();
+        assert_eq!(items, plain);
+
+        let unseeded = loot.loot_with_paths(&drops[..1]);
+        assert_eq!(unseeded.len(), 3);
+        assert!(unseeded.iter().all(|(path, _)| path.starts_with("weapons")));
+    }
+
+    #[test]
+    fn success_loot_logged() {
+        let loot = stuffed();
+        let drops = [
             DropBuilder::new()
                 .path("weapons")
-                .luck(luck_for_weapons)
+                .luck(1.0)
+                .stack(2..=2)
+                .build(),
+            DropBuilder::new()
+                .path("equipment")
                 .anydepth()
+                .guaranteed(true)
                 .build(),
         ];
 
-        let rolls = 100_000;
-        let f_rolls: f64 = Into::<f64>::into(rolls);
-        let mut overall_count = 0;
-        let mut overall_rewards = HashMap::<&'static str, i32>::new();
+        let (rewards, log) = loot.loot_logged(&drops, 42);
+
+        assert_eq!(log.seed, 42);
+        assert_eq!(log.drops.len(), 2);
+        assert_eq!(log.entries.len(), rewards.len());
+        assert_eq!(log.entries.len(), 3);
+        assert_eq!(log.entries[0].drop_index, 0);
+        assert_eq!(log.entries[0].path, "weapons");
+        assert_eq!(log.entries[2].drop_index, 1);
+        assert!(log.entries[2].path.starts_with("equipment"));
+        assert!(log
+            .entries
+            .iter()
+            .zip(&rewards)
+            .all(|(entry, item)| entry.name == item.name));
+
+        let plain = loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(42));
+        assert_eq!(plain, rewards);
+    }
 
-        (0..rolls).for_each(|_| {
-            loot.loot(&drops).iter().for_each(|r| {
-                let current = match overall_rewards.get(r.name) {
-                    Some(number) => number.clone(),
-                    None => 0,
-                };
-                overall_rewards.insert(r.name, current + 1);
-                overall_count += 1;
-            })
-        });
+    #[cfg(feature = "serde")]
+    #[test]
+    fn success_loot_log_serde() {
+        let loot = stuffed();
+        let drops = [DropBuilder::new().path("weapons").stack(3..=3).build()];
 
-        let gloves = overall_rewards.get("Gloves");
-        let boots = overall_rewards.get("Boots");
-        let jacket = overall_rewards.get("Jacket");
-        let pads = overall_rewards.get("Pads");
-        let armband = overall_rewards.get("ArmBand");
-        let patch = overall_rewards.get("Patch");
-        let bat = overall_rewards.get("Bat");
-        let uzi = overall_rewards.get("Uzi");
+        let (_, log) = loot.loot_logged(&drops, 42);
+        let json = serde_json::to_string(&log).unwrap();
 
-        assert_ne!(gloves, None, "There should be some Gloves");
-        assert_ne!(boots, None, "There should be some Boots");
-        assert_ne!(jacket, None, "There should be some Jacket");
-        assert_ne!(pads, None, "There should be some Pads");
-        assert_ne!(armband, None, "There should be some ArmBand");
-        assert_ne!(patch, None, "There should be some Patch");
-        assert_ne!(bat, None, "There should be some Bat");
-        assert_ne!(uzi, None, "There should be some Uzi");
+        assert!(json.contains("\"seed\":42"));
+        assert!(json.contains("\"path\":\"weapons\""));
+    }
 
-        let zero = &0;
-        let equipment = 0
-            + overall_rewards.get("Gloves").unwrap_or(zero)
-            + overall_rewards.get("Boots").unwrap_or(zero)
-            + overall_rewards.get("Jacket").unwrap_or(zero)
-            + overall_rewards.get("Pads").unwrap_or(zero)
-            + overall_rewards.get("ArmBand").unwrap_or(zero)
-            + overall_rewards.get("Patch").unwrap_or(zero);
+    #[test]
+    fn success_loot_shuffled() {
+        let loot = stuffed();
+        let drops = [
+            DropBuilder::new()
+                .path("weapons")
+                .luck(1.0)
+                .stack(3..=3)
+                .build(),
+            DropBuilder::new()
+                .path("equipment")
+                .luck(1.0)
+                .stack(3..=3)
+                .build(),
+        ];
+        fn names(items: Vec<Item>) -> Vec<Cow<str>> {
+            items.into_iter().map(|i| i.name).collect()
+        }
 
-        let weapons = 0
-            + overall_rewards.get("Bat").unwrap_or(zero)
-            + overall_rewards.get("Uzi").unwrap_or(zero);
+        let shuffled = names(loot.loot_shuffled(&drops, &mut ChaCha20Rng::seed_from_u64(123)));
+        let again = names(loot.loot_shuffled(&drops, &mut ChaCha20Rng::seed_from_u64(123)));
+        let plain = names(loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123)));
 
-        assert_eq!(equipment + weapons, overall_count);
+        assert_eq!(shuffled, again, "Should be stable for a seed");
 
-        let theory = f_rolls * Into::<f64>::into(luck_for_equipment);
-        let expected_equipment = (theory * 0.7)..(theory * 1.6);
+        let mut sorted = shuffled.clone();
+        let mut plain_sorted = plain.clone();
+        sorted.sort();
+        plain_sorted.sort();
 
-        let theory = f_rolls * Into::<f64>::into(luck_for_weapons);
-        let expected_weapons = (theory * 0.7)..(theory * 1.6);
+        assert_eq!(sorted, plain_sorted, "Should yield the same items");
+    }
 
-        assert_eq!(
-            expected_equipment.contains(&equipment.into()),
-            true,
-            "There should be enough equipment"
-        );
-        assert_eq!(
-            expected_weapons.contains(&weapons.into()),
-            true,
-            "There should be enough weapons"
+    #[test]
+    fn success_loot_sets() {
+        let loot = stuffed();
+        let sets = [DropSet {
+            path: Some("equipment"),
+            items: vec!["Gloves", "Jacket"],
+            luck: 0.5,
+        }];
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+        let mut complete = 0;
+
+        (0..100).for_each(|_| {
+            let rewards = loot.loot_sets(&sets, rng);
+
+            match rewards.len() {
+                0 => (),
+                2 => {
+                    assert_eq!(rewards[0].name, "Gloves");
+                    assert_eq!(rewards[1].name, "Jacket");
+                    complete += 1;
+                }
+                _ => panic!("Should yield the whole set or nothing"),
+            }
+        });
+
+        assert!(
+            (25..75).contains(&complete),
+            "Should yield about half the sets"
         );
+
+        let missing = [DropSet {
+            items: vec!["Gloves", "Crown"],
+            ..Default::default()
+        }];
+        assert!(loot.loot_sets(&missing, rng).is_empty());
     }
 
     #[test]
-    fn success_loot_seeded() {
-        let loot = stuffed();
-        let drops = [
-            DropBuilder::new().path("equipment").anydepth().build(),
-            DropBuilder::new().path("weapons").anydepth().build(),
-        ];
+    fn success_loot_closure_modifier() {
+        let bonus = 10;
+        let level = format!("+{}", bonus * 2);
+        let mut loot = Lootr::new();
 
-        let rewards = loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+        loot.add_modifier(|source: Item| {
+            source.extend(
+                source.name.clone(),
+                Props::from([("damage", level.as_str())]),
+            )
+        })
+        .add(Item::a("crown"));
 
-        (0..10).for_each(|_| {
-            let nloot = stuffed();
-            let nrewards = nloot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+        let picked = loot.loot(&[DropBuilder::new().luck(1.0).modified().build()]);
 
-            nrewards.iter().enumerate().for_each(|(i, r)| {
-                assert_eq!(
-                    r.name,
-                    rewards.get(i).unwrap().name,
-                    "Should return same elements"
-                )
-            });
-        });
+        assert_eq!(picked.len(), 1);
+        assert_eq!(picked[0].get_prop("damage"), Some("+20"));
+    }
+
+    #[test]
+    fn success_loot_rename_modifiers() {
+        let mut loot = Lootr::from(vec![Item::a("Sword")]);
+        loot.add_branch("cursed", Lootr::from(vec![Item::an("Axe")]));
+        loot.add_modifier(modifiers::prefix("Flaming "));
+        loot.branch_mut("cursed")
+            .unwrap()
+            .add_modifier(modifiers::suffix(" of Doom"));
+
+        let drop = DropBuilder::new().depth(0).luck(1.0).modified().build();
+        let picked = loot.loot(&[drop]);
+        assert_eq!(picked[0].name, "Flaming Sword");
+
+        let drop = DropBuilder::new()
+            .path("cursed")
+            .luck(1.0)
+            .modified()
+            .build();
+        let picked = loot.loot(&[drop]);
+        assert!(["Flaming Axe", "Axe of Doom"].contains(&picked[0].name.as_ref()));
+
+        let picked = loot.loot(&[DropBuilder::new().depth(0).luck(1.0).build()]);
+        assert_eq!(picked[0].name, "Sword", "Should only rename modified drops");
     }
 
     #[test]
@@ -397,7 +2871,7 @@ mod tests {
         let mut loot = Lootr::new();
 
         fn with_strength(source: Item) -> Item {
-            source.extend(source.name, Props::from([("strength", "+10")]))
+            source.extend(source.name.clone(), Props::from([("strength", "+10")]))
         }
 
         loot.add_modifier(with_strength).add(Item::a("crown"));
@@ -409,6 +2883,7 @@ mod tests {
                 depth: 1,
                 stack: 1..=1,
                 modify: false,
+                ..Default::default()
             },
             Drop {
                 path: ROOT,
@@ -416,18 +2891,192 @@ mod tests {
                 depth: 1,
                 stack: 1..=1,
                 modify: true,
+                ..Default::default()
             },
         ]);
 
         let first = &picked.first().unwrap().clone();
         let last = &picked.last().unwrap().clone();
 
-        assert_eq!(first.has_prop("strength"), false);
+        assert!(!first.has_prop("strength"));
 
-        assert_eq!(last.has_prop("strength"), true);
+        assert!(last.has_prop("strength"));
         assert_eq!(last.get_prop("strength").unwrap().to_owned(), "+10");
     }
 
+    #[test]
+    fn success_loot_with_inventory() {
+        let loot = stuffed();
+        let drops = [DropBuilder::new()
+            .path("weapons")
+            .luck(1.0)
+            .requires("golden_key")
+            .build()];
+
+        let locked = loot.loot_with_inventory(
+            &drops,
+            &HashSet::from(["rusty_key"]),
+            &mut ChaCha20Rng::seed_from_u64(123),
+        );
+        assert!(locked.is_empty(), "Should not loot without the key");
+
+        let unlocked = loot.loot_with_inventory(
+            &drops,
+            &HashSet::from(["rusty_key", "golden_key"]),
+            &mut ChaCha20Rng::seed_from_u64(123),
+        );
+        assert_eq!(unlocked.len(), 1, "Should loot with the key");
+    }
+
+    #[test]
+    fn success_loot_sorted_modifiers() {
+        fn with_strength(source: Item) -> Item {
+            source.extend(source.name.clone(), Props::from([("strength", "+10")]))
+        }
+
+        fn with_charisma(source: Item) -> Item {
+            source.extend(source.name.clone(), Props::from([("charisma", "+10")]))
+        }
+
+        let mut loot = Lootr::from(vec![Item::a("crown")]);
+        loot.sort_modifiers(true)
+            .add_named_modifier("strength", with_strength)
+            .add_named_modifier("charisma", with_charisma);
+
+        let mut reloaded = Lootr::from(vec![Item::a("crown")]);
+        reloaded
+            .sort_modifiers(true)
+            .add_named_modifier("charisma", with_charisma)
+            .add_named_modifier("strength", with_strength);
+
+        let drops = [DropBuilder::new()
+            .luck(1.0)
+            .stack(20..=20)
+            .modified()
+            .build()];
+
+        let rewards = loot.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+        let nrewards = reloaded.loot_seeded(&drops, &mut ChaCha20Rng::seed_from_u64(123));
+
+        assert_eq!(rewards.len(), 20);
+        rewards.iter().zip(nrewards.iter()).for_each(|(r, n)| {
+            assert_eq!(
+                r.has_prop("strength"),
+                n.has_prop("strength"),
+                "Should apply the same modifiers"
+            );
+        });
+    }
+
+    #[test]
+    fn success_drop_report() {
+        let loot = stuffed();
+        let drops = [
+            DropBuilder::new()
+                .path("weapons")
+                .luck(1.0)
+                .stack(1..=3)
+                .build(),
+            DropBuilder::new()
+                .path("equipment")
+                .luck(0.5)
+                .anydepth()
+                .build(),
+        ];
+
+        let report = loot.drop_report(&drops, 1_000, &mut ChaCha20Rng::seed_from_u64(123));
+
+        assert_eq!(report.samples, 1_000);
+        assert!(report.expected_items > 0.0, "Should expect some items");
+        assert_eq!(report.expected_stacks.len(), 2);
+        assert!((1.0..=3.0).contains(&report.expected_stacks[0]));
+        assert!(report.item_probabilities.contains_key("Uzi"));
+        assert!(fmt::format(format_args!("{}", report)).contains("Uzi"));
+    }
+
+    #[test]
+    fn success_loot_branch_modifier() {
+        let mut loot = stuffed();
+
+        fn with_fire(source: Item) -> Item {
+            source.extend(source.name.clone(), Props::from([("element", "fire")]))
+        }
+
+        loot.branch_mut("weapons").unwrap().add_modifier(with_fire);
+
+        let drops = [
+            DropBuilder::new()
+                .path("weapons")
+                .luck(1.0)
+                .modified()
+                .build(),
+            DropBuilder::new()
+                .path("equipment")
+                .luck(1.0)
+                .anydepth()
+                .modified()
+                .build(),
+        ];
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        (0..100).for_each(|_| {
+            loot.loot_seeded(&drops, rng).iter().for_each(|item| {
+                let is_weapon = ["Bat", "Uzi"].contains(&item.name.as_ref());
+                assert_eq!(
+                    item.has_prop("element"),
+                    is_weapon,
+                    "Only weapons should be modified"
+                );
+            });
+        });
+    }
+
+    #[test]
+    fn success_salvage() {
+        let rules = SalvageRules::new()
+            .rule("attack", Item::a("metal"), 1..=3)
+            .rule("magic_power", Item::a("crystal"), 1..=1);
+
+        let sword = Item::from("sword", Props::from([("attack", "10")]));
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        (0..10).for_each(|_| {
+            let components = salvage(&sword, &rules, rng);
+
+            assert!((1..=3).contains(&components.len()));
+            assert!(components.iter().all(|c| c.name == "metal"));
+        });
+
+        assert!(salvage(&Item::a("rock"), &rules, rng).is_empty());
+    }
+
+    #[test]
+    fn success_salvage_by_tag() {
+        let rules = SalvageRules::new()
+            .rule("attack", Item::a("metal"), 1..=1)
+            .tag_rule("wooden", Item::a("plank"), 2..=2);
+        let rng = &mut ChaCha20Rng::seed_from_u64(123);
+
+        let mut club = Item::from("club", Props::from([("attack", "4")]));
+        club.add_tag("wooden");
+
+        let names: Vec<Cow<str>> = salvage(&club, &rules, rng)
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["metal", "plank", "plank"]);
+
+        let mut stick = Item::a("stick");
+        stick.add_tag("wooden");
+        assert_eq!(salvage(&stick, &rules, rng).len(), 2);
+
+        let wooden = Item::from("wooden", Props::from([("wooden", "yes")]));
+        assert!(
+            salvage(&wooden, &rules, rng).is_empty(),
+            "Should not match tag rules on props"
+        );
+    }
+
     ////////////////////////////////////////////////////
 
     fn stuffed<'a>() -> Lootr<'a> {